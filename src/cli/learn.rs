@@ -0,0 +1,148 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use crate::midi::controller::midir::{MidirBasedReceiver, MidirBasedSender};
+use crate::midi::model::{DataByte, MidiMessage, MidiReceiver, MidiSender};
+use crate::worker::actor::PadId;
+
+use super::Target;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Name of the controller. Defaults to `controller_name` from the configuration file.
+    #[arg(short, long)]
+    pub controller: Option<String>,
+    /// Maximal number of pads to learn. Pressing the first learned pad again finishes earlier.
+    #[arg(short = 'n', long, default_value_t = 16)]
+    pub pads: usize,
+    /// Color used to confirm that pad has been learned.
+    #[arg(long, default_value = "0x60", value_parser = super::parse_data_byte)]
+    pub color: DataByte,
+    /// Writes complete configuration of the controller with named pads in its `layout`,
+    /// instead of `mappings` skeleton, which is only a fragment of the controller entry.
+    #[arg(long)]
+    pub profile: bool,
+    /// File where the learned configuration is written.
+    #[arg(short, long, default_value = "learned_config.yaml")]
+    pub output: PathBuf,
+}
+
+pub fn run(args: &Args, target: &Target) -> anyhow::Result<()> {
+    let controller = target.controller.as_str();
+    let receiver = MidirBasedReceiver::new(controller, &target.input_port)?;
+    let sender = MidirBasedSender::new(controller, &target.output_port)?;
+
+    println!("Connected to {controller}. Press pads one at a time.");
+    println!("Press the first learned pad again to finish.");
+
+    let mut learned: Vec<PadId> = Vec::new();
+    while learned.len() < args.pads {
+        println!("Waiting for pad #{}...", learned.len() + 1);
+        let pad_id = wait_for_press(&receiver)?;
+
+        if learned.first() == Some(&pad_id) {
+            break;
+        }
+        if learned.contains(&pad_id) {
+            println!("Pad has been already learned. Press another one.");
+            continue;
+        }
+
+        sender.send_and_forget(MidiMessage {
+            status: pad_id.status,
+            fst_data_byte: pad_id.fst_data_byte,
+            snd_data_byte: args.color,
+        });
+        println!(
+            "Learned pad status: {:#04X}, fst_data_byte: {:#04X}.",
+            pad_id.status.as_u8(),
+            pad_id.fst_data_byte.as_u8()
+        );
+        learned.push(pad_id);
+    }
+
+    let yaml = if args.profile {
+        render_layout(controller, &learned)
+    } else {
+        render_mappings(&learned)
+    };
+    std::fs::write(&args.output, yaml)?;
+    println!(
+        "Written {} pad(s) to {}.",
+        learned.len(),
+        args.output.display()
+    );
+
+    Ok(())
+}
+
+fn wait_for_press<MR: MidiReceiver>(receiver: &MR) -> anyhow::Result<PadId> {
+    loop {
+        let msg = receiver
+            .poll()
+            .ok_or_else(|| anyhow::Error::msg("MIDI input connection has been closed."))?;
+        if msg.is_pressed() {
+            return Ok(PadId::from_message(&msg));
+        }
+    }
+}
+
+fn render_mappings(pads: &[PadId]) -> String {
+    let mut yaml = String::from("mappings:\n");
+    for pad_id in pads {
+        let _ = write!(
+            yaml,
+            "  - status: {:#04X}\n    fst_data_byte: {:#04X}\n    every_seconds: 10\n    type: Script\n    command: \"true\"\n",
+            pad_id.status.as_u8(),
+            pad_id.fst_data_byte.as_u8()
+        );
+    }
+    yaml
+}
+
+/// Complete configuration of the controller, with learned pads named in its layout.
+/// Palette is the one of the example configuration, and pads run a placeholder script.
+fn render_layout(controller: &str, pads: &[PadId]) -> String {
+    let mut yaml = format!(
+        "controllers:\n  - controller_name: {controller}\n    color_palette:\n      ok: 0x60\n      action_triggerred: 0x50\n      transient_error: 0x40\n      not_ok: 0x30\n      initial: 0x20\n      paused: 0x10\n    layout:\n      pads:\n"
+    );
+    for (idx, pad_id) in pads.iter().enumerate() {
+        let _ = write!(
+            yaml,
+            "        - name: pad-{}\n          status: {:#04X}\n          fst_data_byte: {:#04X}\n",
+            idx + 1,
+            pad_id.status.as_u8(),
+            pad_id.fst_data_byte.as_u8()
+        );
+    }
+    yaml.push_str("    mappings:\n");
+    for idx in 0..pads.len() {
+        let _ = write!(
+            yaml,
+            "      - pad: pad-{}\n        every_seconds: 10\n        type: Script\n        command: \"true\"\n",
+            idx + 1
+        );
+    }
+    yaml
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::configuration;
+    use crate::midi::model::Status;
+
+    use super::*;
+
+    #[test]
+    fn profile_is_loadable_configuration() {
+        let pads = [0x24, 0x25].map(|fst_data_byte| PadId {
+            status: Status::from_u8(0x90).unwrap(),
+            fst_data_byte: DataByte::from_u8(fst_data_byte).unwrap(),
+        });
+        let yaml = render_layout("DDJ-XP2", &pads);
+
+        let parsed = configuration::parse(serde_yaml::from_str(&yaml).unwrap()).unwrap();
+        assert_eq!(parsed.controllers.len(), 1);
+        assert_eq!(parsed.controllers[0].pad_configs.len(), 2);
+    }
+}
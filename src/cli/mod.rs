@@ -0,0 +1,36 @@
+use crate::midi::model::{DataByte, PortSelector};
+
+pub mod learn;
+pub mod monitor;
+pub mod ports;
+
+/// Controller together with its ports, selected by the command line or the configuration file.
+pub struct Target {
+    pub controller: String,
+    pub input_port: PortSelector,
+    pub output_port: PortSelector,
+}
+
+impl Target {
+    pub fn named(controller: &str) -> Target {
+        Target {
+            controller: controller.to_string(),
+            input_port: PortSelector::Contains(controller.to_string()),
+            output_port: PortSelector::Contains(controller.to_string()),
+        }
+    }
+}
+
+/// Parses data byte passed as decimal (`96`) or hexadecimal (`0x60`) number.
+pub fn parse_data_byte(value: &str) -> Result<DataByte, String> {
+    let parsed = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => value.parse::<u8>(),
+    }
+    .map_err(|e| format!("Invalid data byte '{value}'. Reason: {e}"))?;
+
+    DataByte::try_from(parsed)
+}
@@ -0,0 +1,87 @@
+use crossbeam_channel as cch;
+
+use crate::midi::controller::midir;
+use crate::midi::model::PRESSED;
+
+use super::Target;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Name of the controller. Defaults to `controller_name` from the configuration file.
+    #[arg(short, long)]
+    pub controller: Option<String>,
+}
+
+pub fn run(target: &Target) -> anyhow::Result<()> {
+    let controller = target.controller.as_str();
+    let (sender, receiver) = cch::unbounded();
+    let _connection =
+        midir::connect_input(controller, &target.input_port, move |timestamp, message| {
+            let _ = sender.send((timestamp, message.to_vec()));
+        })?;
+
+    println!("Monitoring {controller}. Press Ctrl+C to stop.");
+    for (timestamp, message) in receiver {
+        let raw: Vec<String> = message.iter().map(|b| format!("{b:02X}")).collect();
+        println!(
+            "[{:>14.6}] {:<9} {}",
+            timestamp as f64 / 1_000_000.0,
+            raw.join(" "),
+            describe(&message)
+        );
+    }
+
+    Ok(())
+}
+
+fn describe(message: &[u8]) -> String {
+    let Some(&status) = message.first() else {
+        return String::from("Empty message");
+    };
+    let channel = (status & 0x0F) + 1;
+
+    match (status & 0xF0, &message[1..]) {
+        (0x80, &[note, velocity]) => {
+            format!(
+                "Note Off         ch {channel:>2} note {note:>3} velocity {velocity:>3}{}",
+                pad(message)
+            )
+        }
+        (0x90, &[note, velocity]) => {
+            format!(
+                "Note On          ch {channel:>2} note {note:>3} velocity {velocity:>3}{}",
+                pad(message)
+            )
+        }
+        (0xA0, &[note, pressure]) => {
+            format!("Poly Aftertouch  ch {channel:>2} note {note:>3} pressure {pressure:>3}")
+        }
+        (0xB0, &[controller, value]) => {
+            format!(
+                "Control Change   ch {channel:>2} cc {controller:>5} value {value:>6}{}",
+                pad(message)
+            )
+        }
+        (0xC0, &[program]) => format!("Program Change   ch {channel:>2} program {program:>3}"),
+        (0xD0, &[pressure]) => format!("Channel Pressure ch {channel:>2} pressure {pressure:>3}"),
+        (0xE0, &[lsb, msb]) => {
+            let value = ((msb as i16) << 7 | lsb as i16) - 8192;
+            format!("Pitch Bend       ch {channel:>2} value {value:>6}")
+        }
+        (0xF0, _) => format!("System message ({} bytes)", message.len()),
+        _ => String::from("Unknown message"),
+    }
+}
+
+/// Pad identification in the same notation as used by the configuration file.
+fn pad(message: &[u8]) -> String {
+    let pressed = if message[2] == PRESSED.as_u8() {
+        " pressed"
+    } else {
+        ""
+    };
+    format!(
+        "  [status: {:#04X}, fst_data_byte: {:#04X}{pressed}]",
+        message[0], message[1]
+    )
+}
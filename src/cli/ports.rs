@@ -0,0 +1,42 @@
+use crate::midi::controller::midir;
+use crate::midi::model::PortSelector;
+
+use super::Target;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Name of the controller. Defaults to `controller_name` from the configuration file.
+    #[arg(short, long)]
+    pub controller: Option<String>,
+}
+
+pub fn run(target: Option<&Target>) -> anyhow::Result<()> {
+    let input_port = target.map(|t| &t.input_port);
+    let output_port = target.map(|t| &t.output_port);
+    print_ports("Input", &midir::input_port_names()?, input_port);
+    println!();
+    print_ports("Output", &midir::output_port_names()?, output_port);
+    Ok(())
+}
+
+fn print_ports(direction: &str, port_names: &[String], selector: Option<&PortSelector>) {
+    println!("{direction} ports:");
+    if port_names.is_empty() {
+        println!("    <none>");
+    }
+
+    let selected = selector.map(|s| s.select(port_names));
+    for (idx, port_name) in port_names.iter().enumerate() {
+        let marker = match &selected {
+            Some(Ok(selected_idx)) if *selected_idx == idx => '*',
+            _ => ' ',
+        };
+        println!("  {marker} {idx}: {port_name}");
+    }
+
+    match (selector, selected) {
+        (Some(selector), Some(Ok(_))) => println!("  (* - port selected by {selector})"),
+        (_, Some(Err(e))) => println!("  {e}"),
+        _ => {}
+    }
+}
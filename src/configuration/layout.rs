@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail};
+
+use crate::midi::model::{DataByte, Status};
+use crate::worker::actor::PadId;
+
+// YAML specific configuration
+
+/// Rectangular block of pads, e.g. single pad mode of the controller.
+/// Pads are numbered row by row, starting from `first_data_byte`.
+#[derive(Debug, serde::Deserialize)]
+pub struct Grid {
+    pub page: u8,
+    pub status: Status,
+    pub first_data_byte: DataByte,
+    pub rows: u8,
+    pub columns: u8,
+    /// Difference between first data bytes of two consecutive rows.
+    /// Defaults to number of columns.
+    pub row_stride: Option<u8>,
+}
+
+#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug, serde::Deserialize)]
+pub struct GridPosition {
+    pub page: u8,
+    pub row: u8,
+    pub column: u8,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum PadLocation {
+    Position(GridPosition),
+    Id(PadId),
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct NamedPad {
+    pub name: String,
+    #[serde(flatten)]
+    pub location: PadLocation,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct Layout {
+    #[serde(default = "Vec::new")]
+    pub grids: Vec<Grid>,
+    #[serde(default = "Vec::new")]
+    pub pads: Vec<NamedPad>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum PadSelector {
+    Name(String),
+    Position(GridPosition),
+}
+
+/// The way mapping points to a physical pad.
+/// Either through the layout (`pad: redis-prod`, `pad: {page: 0, row: 1, column: 2}`)
+/// or directly with `status` and `fst_data_byte`.
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum PadRef {
+    Layout { pad: PadSelector },
+    Id(PadId),
+}
+
+// Parsed part - from configuration to application specific
+
+pub struct ResolvedLayout {
+    positions: HashMap<GridPosition, PadId>,
+    names: HashMap<String, PadId>,
+}
+
+impl ResolvedLayout {
+    pub fn resolve(layout: Layout) -> anyhow::Result<ResolvedLayout> {
+        let mut positions = HashMap::new();
+        for grid in layout.grids {
+            Self::expand_grid(&grid, &mut positions)?;
+        }
+
+        let mut names = HashMap::new();
+        for named_pad in layout.pads {
+            let pad_id = match named_pad.location {
+                PadLocation::Id(pad_id) => pad_id,
+                PadLocation::Position(position) => {
+                    Self::lookup_position(&positions, &position)?.clone()
+                }
+            };
+            if names.insert(named_pad.name.clone(), pad_id).is_some() {
+                bail!("Pad name '{}' is defined more than once.", named_pad.name);
+            }
+        }
+
+        Ok(ResolvedLayout { positions, names })
+    }
+
+    pub fn pad_id(&self, pad_ref: PadRef) -> anyhow::Result<PadId> {
+        match pad_ref {
+            PadRef::Id(pad_id) => Ok(pad_id),
+            PadRef::Layout {
+                pad: PadSelector::Position(position),
+            } => Self::lookup_position(&self.positions, &position).cloned(),
+            PadRef::Layout {
+                pad: PadSelector::Name(name),
+            } => self
+                .names
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| anyhow!("Pad '{name}' is not defined in the layout.")),
+        }
+    }
+
+    fn lookup_position<'a>(
+        positions: &'a HashMap<GridPosition, PadId>,
+        position: &GridPosition,
+    ) -> anyhow::Result<&'a PadId> {
+        positions.get(position).ok_or_else(|| {
+            anyhow!(
+                "Position (page: {}, row: {}, column: {}) is outside of defined grids.",
+                position.page,
+                position.row,
+                position.column
+            )
+        })
+    }
+
+    fn expand_grid(
+        grid: &Grid,
+        positions: &mut HashMap<GridPosition, PadId>,
+    ) -> anyhow::Result<()> {
+        let row_stride = grid.row_stride.unwrap_or(grid.columns) as u16;
+        for row in 0..grid.rows {
+            for column in 0..grid.columns {
+                let data_byte =
+                    grid.first_data_byte.as_u8() as u16 + row as u16 * row_stride + column as u16;
+                let fst_data_byte = u8::try_from(data_byte)
+                    .ok()
+                    .and_then(DataByte::from_u8)
+                    .ok_or_else(|| {
+                        anyhow!("Grid on page {} exceeds data byte range.", grid.page)
+                    })?;
+
+                let position = GridPosition {
+                    page: grid.page,
+                    row,
+                    column,
+                };
+                let pad_id = PadId {
+                    status: grid.status,
+                    fst_data_byte,
+                };
+                if positions.insert(position, pad_id).is_some() {
+                    bail!(
+                        "Grids overlap at (page: {}, row: {row}, column: {column}).",
+                        grid.page
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(yaml: &str) -> anyhow::Result<ResolvedLayout> {
+        ResolvedLayout::resolve(serde_yaml::from_str(yaml).unwrap())
+    }
+
+    fn pad_ref(yaml: &str) -> PadRef {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn pad_id(status: u8, fst_data_byte: u8) -> PadId {
+        PadId {
+            status: Status::from_u8(status).unwrap(),
+            fst_data_byte: DataByte::from_u8(fst_data_byte).unwrap(),
+        }
+    }
+
+    const LAYOUT: &str = r#"
+grids:
+  - page: 0
+    status: 0x97
+    first_data_byte: 0x00
+    rows: 2
+    columns: 4
+    row_stride: 8
+pads:
+  - name: redis-prod
+    page: 0
+    row: 1
+    column: 2
+  - name: shift
+    status: 0x90
+    fst_data_byte: 0x3F
+"#;
+
+    #[test]
+    fn resolves_positions_and_names() {
+        let layout = resolve(LAYOUT).unwrap();
+
+        assert_eq!(
+            layout
+                .pad_id(pad_ref("pad: {page: 0, row: 1, column: 3}"))
+                .unwrap(),
+            pad_id(0x97, 0x0B)
+        );
+        assert_eq!(
+            layout.pad_id(pad_ref("pad: redis-prod")).unwrap(),
+            pad_id(0x97, 0x0A)
+        );
+        assert_eq!(
+            layout.pad_id(pad_ref("pad: shift")).unwrap(),
+            pad_id(0x90, 0x3F)
+        );
+        assert_eq!(
+            layout
+                .pad_id(pad_ref("{status: 0x91, fst_data_byte: 0x01}"))
+                .unwrap(),
+            pad_id(0x91, 0x01)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_pads() {
+        let layout = resolve(LAYOUT).unwrap();
+
+        assert!(layout.pad_id(pad_ref("pad: unknown")).is_err());
+        assert!(layout
+            .pad_id(pad_ref("pad: {page: 0, row: 2, column: 0}"))
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        let overlapping = r#"
+grids:
+  - {page: 0, status: 0x97, first_data_byte: 0x00, rows: 1, columns: 2}
+  - {page: 0, status: 0x97, first_data_byte: 0x10, rows: 1, columns: 2}
+"#;
+        assert!(resolve(overlapping).is_err());
+
+        let out_of_range = r#"
+grids:
+  - {page: 0, status: 0x97, first_data_byte: 0x7E, rows: 1, columns: 4}
+"#;
+        assert!(resolve(out_of_range).is_err());
+
+        let duplicate_name = r#"
+pads:
+  - {name: a, status: 0x90, fst_data_byte: 0x01}
+  - {name: a, status: 0x90, fst_data_byte: 0x02}
+"#;
+        assert!(resolve(duplicate_name).is_err());
+    }
+}
@@ -0,0 +1,1426 @@
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::kubernetes::model as k8s_model;
+use crate::metrics;
+use crate::midi::model as midi_model;
+use crate::worker::actor;
+use crate::worker::alertmanager as alertmanager_handler;
+use crate::worker::certificate as certificate_handler;
+use crate::worker::composite;
+use crate::worker::file as file_handler;
+use crate::worker::k8s as k8s_handler;
+use crate::worker::log_tail as log_tail_handler;
+use crate::worker::prometheus as prometheus_handler;
+use crate::worker::script as script_handler;
+use crate::worker::system as system_handler;
+use crate::worker::tcp as tcp_handler;
+use crate::worker::webhook;
+
+pub mod layout;
+
+// YAML specific configuration
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type")]
+pub enum HandlerConfig {
+    K8S(K8SConfig),
+    /// Checks all Deployments, StatefulSets and Pods in the namespace.
+    K8SNamespace {
+        #[serde(flatten)]
+        namespace_id: k8s_model::NamespaceId,
+        label_selector: Option<String>,
+        #[serde(default)]
+        max_unhealthy: usize,
+        depl_in_progress: midi_model::DataByte,
+        unknown: midi_model::DataByte,
+    },
+    /// Counts warning events of the workload and its pods in a sliding window.
+    K8SEvents {
+        #[serde(flatten)]
+        workload_id: k8s_model::DeploymentId,
+        #[serde(default = "default_event_reasons")]
+        reasons: Vec<String>,
+        window_seconds: u64,
+        #[serde(default)]
+        threshold: u32,
+    },
+    /// Checks Ready condition and pressure conditions of all nodes in the cluster.
+    K8SNodes {
+        context: k8s_model::ClusterContext,
+        degraded: midi_model::DataByte,
+    },
+    /// Checks whether the API server is reachable and ready.
+    K8SApiServer {
+        context: k8s_model::ClusterContext,
+        degraded: midi_model::DataByte,
+    },
+    Script {
+        #[serde(default = "HashMap::new")]
+        envs: HashMap<String, String>,
+        command: String,
+        #[serde(default = "Vec::new")]
+        args: Vec<String>,
+    },
+    /// Runs PromQL instant query.
+    Prometheus(PrometheusConfig),
+    /// Checks active alerts of Alertmanager.
+    Alertmanager(AlertmanagerConfig),
+    /// Checks whether the port accepts connections.
+    #[serde(rename = "TCP")]
+    Tcp(TcpConfig),
+    /// Checks the number of days remaining until the certificate expires.
+    Certificate(CertificateConfig),
+    /// Checks resources of the machine running the controller.
+    System(SystemConfig),
+    /// Checks existence, age and content of files matching the glob pattern.
+    File(FileConfig),
+    /// Displays states pushed to the embedded server with `POST /pads/{name}`.
+    Webhook(WebhookConfig),
+    /// Follows the log file and counts lines matching the pattern in a sliding window.
+    LogTail(LogTailConfig),
+    /// Combines outputs of children handlers with the policy.
+    Composite {
+        policy: composite::Policy,
+        children: Vec<HandlerConfig>,
+    },
+    /// Refers to the handler defined in top level `handlers` section.
+    Shared {
+        handler: String,
+    },
+}
+
+impl HandlerConfig {
+    /// Name of the type, as written in the configuration.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            HandlerConfig::K8S(_) => "K8S",
+            HandlerConfig::K8SNamespace { .. } => "K8SNamespace",
+            HandlerConfig::K8SEvents { .. } => "K8SEvents",
+            HandlerConfig::K8SNodes { .. } => "K8SNodes",
+            HandlerConfig::K8SApiServer { .. } => "K8SApiServer",
+            HandlerConfig::Script { .. } => "Script",
+            HandlerConfig::Prometheus(_) => "Prometheus",
+            HandlerConfig::Alertmanager(_) => "Alertmanager",
+            HandlerConfig::Tcp(_) => "TCP",
+            HandlerConfig::Certificate(_) => "Certificate",
+            HandlerConfig::System(_) => "System",
+            HandlerConfig::File(_) => "File",
+            HandlerConfig::Webhook(_) => "Webhook",
+            HandlerConfig::LogTail(_) => "LogTail",
+            HandlerConfig::Composite { .. } => "Composite",
+            HandlerConfig::Shared { .. } => "Shared",
+        }
+    }
+
+    /// Checked object, e.g. deployment or URL, identifying the pad in the logs.
+    pub fn target(&self) -> String {
+        match self {
+            HandlerConfig::K8S(config) => config.deployment_id.to_string(),
+            HandlerConfig::K8SNamespace { namespace_id, .. } => namespace_id.to_string(),
+            HandlerConfig::K8SEvents { workload_id, .. } => workload_id.to_string(),
+            HandlerConfig::K8SNodes { context, .. }
+            | HandlerConfig::K8SApiServer { context, .. } => context.to_string(),
+            HandlerConfig::Script { command, .. } => command.clone(),
+            HandlerConfig::Prometheus(config) => config.url.clone(),
+            HandlerConfig::Alertmanager(config) => config.url.clone(),
+            HandlerConfig::Tcp(config) => format!("{}:{}", config.host, config.port),
+            HandlerConfig::Certificate(config) => match &config.source {
+                CertificateSourceConfig::File { file } => file.display().to_string(),
+                CertificateSourceConfig::Endpoint { host, port, .. } => format!("{host}:{port}"),
+            },
+            HandlerConfig::System(config) => match config {
+                SystemConfig::Disk { mount, .. } => mount.display().to_string(),
+                SystemConfig::Memory { .. } => "memory".to_string(),
+                SystemConfig::Load { .. } => "load".to_string(),
+                SystemConfig::Process { name, .. } => name.clone(),
+            },
+            HandlerConfig::File(config) => config.path.clone(),
+            HandlerConfig::Webhook(config) => config.name.clone(),
+            HandlerConfig::LogTail(config) => config.path.display().to_string(),
+            HandlerConfig::Composite { children, .. } => children
+                .iter()
+                .map(HandlerConfig::target)
+                .collect::<Vec<_>>()
+                .join(", "),
+            HandlerConfig::Shared { handler } => handler.clone(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct K8SConfig {
+    #[serde(flatten)]
+    deployment_id: k8s_model::DeploymentId,
+    #[serde(default)]
+    replicas: k8s_model::ReplicaThresholds,
+    depl_in_progress: midi_model::DataByte,
+    unknown: midi_model::DataByte,
+    degraded: Option<midi_model::DataByte>,
+    #[serde(default)]
+    check_pods: bool,
+    crashing: Option<midi_model::DataByte>,
+    /// Watches the deployment instead of checking it periodically.
+    #[serde(default)]
+    watch: bool,
+}
+
+fn default_event_reasons() -> Vec<String> {
+    [
+        "BackOff",
+        "FailedScheduling",
+        "OOMKilled",
+        "OOMKilling",
+        "Unhealthy",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PrometheusConfig {
+    /// Base URL of Prometheus, e.g. `http://prometheus:9090`.
+    url: String,
+    query: String,
+    #[serde(default = "HashMap::new")]
+    headers: HashMap<String, String>,
+    #[serde(default = "default_timeout_seconds")]
+    timeout_seconds: u64,
+    #[serde(default = "default_empty_output")]
+    empty: actor::OutputConfig,
+    #[serde(default = "default_non_empty_output")]
+    non_empty: actor::OutputConfig,
+    #[serde(default = "Vec::new")]
+    thresholds: Vec<ThresholdConfig>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ThresholdConfig {
+    above: f64,
+    output: actor::OutputConfig,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AlertmanagerConfig {
+    /// Base URL of Alertmanager, e.g. `http://alertmanager:9093`.
+    url: String,
+    #[serde(default = "Vec::new")]
+    matchers: Vec<String>,
+    #[serde(default = "HashMap::new")]
+    headers: HashMap<String, String>,
+    #[serde(default = "default_timeout_seconds")]
+    timeout_seconds: u64,
+    #[serde(default = "Vec::new")]
+    severities: Vec<SeverityConfig>,
+    #[serde(default = "default_non_empty_output")]
+    other_severities: actor::OutputConfig,
+    silence_minutes: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SeverityConfig {
+    severity: String,
+    output: actor::OutputConfig,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TcpConfig {
+    host: String,
+    port: u16,
+    #[serde(default = "default_connect_timeout_millis")]
+    timeout_millis: u64,
+    #[serde(default)]
+    tls: bool,
+    /// Name verified against the server certificate. Defaults to `host`.
+    tls_server_name: Option<String>,
+    #[serde(default)]
+    accept_invalid_certs: bool,
+    #[serde(default = "default_not_ok_output")]
+    refused: actor::OutputConfig,
+    #[serde(default = "default_not_ok_output")]
+    handshake_failed: actor::OutputConfig,
+    #[serde(default = "default_timed_out_output")]
+    timed_out: actor::OutputConfig,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum CertificateSourceConfig {
+    File {
+        file: PathBuf,
+    },
+    Endpoint {
+        host: String,
+        #[serde(default = "default_tls_port")]
+        port: u16,
+        /// Name sent in the handshake. Defaults to `host`.
+        server_name: Option<String>,
+    },
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CertificateConfig {
+    #[serde(flatten)]
+    source: CertificateSourceConfig,
+    #[serde(default = "default_connect_timeout_millis")]
+    timeout_millis: u64,
+    #[serde(default = "default_warning_days")]
+    warning_days: i32,
+    #[serde(default = "default_critical_days")]
+    critical_days: i32,
+    warning: actor::OutputConfig,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum SystemConfig {
+    /// Used space of the filesystem in percent.
+    Disk {
+        mount: PathBuf,
+        thresholds: Vec<ThresholdConfig>,
+    },
+    /// Used memory in percent, computed from `MemAvailable`.
+    Memory { thresholds: Vec<ThresholdConfig> },
+    Load {
+        #[serde(default = "default_load_minutes")]
+        minutes: u8,
+        #[serde(default)]
+        per_cpu: bool,
+        thresholds: Vec<ThresholdConfig>,
+    },
+    Process {
+        name: String,
+        #[serde(default = "default_not_ok_output")]
+        missing: actor::OutputConfig,
+    },
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FileConfig {
+    /// Glob pattern, e.g. `/backups/*.marker`.
+    path: String,
+    max_age_minutes: Option<u64>,
+    /// Regular expression, which must not be found in any of the files.
+    contains: Option<String>,
+    #[serde(default)]
+    watch: bool,
+    #[serde(default = "default_not_ok_output")]
+    missing: actor::OutputConfig,
+    #[serde(default = "default_not_ok_output")]
+    stale: actor::OutputConfig,
+    #[serde(default = "default_not_ok_output")]
+    matched: actor::OutputConfig,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct LogTailConfig {
+    path: PathBuf,
+    /// Regular expression of reported lines, e.g. `ERROR|FATAL`.
+    pattern: String,
+    window_seconds: u64,
+    #[serde(default)]
+    threshold: u32,
+    /// Defaults to `window_seconds`.
+    quiet_seconds: Option<u64>,
+    #[serde(default = "default_not_ok_output")]
+    matched: actor::OutputConfig,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct WebhookConfig {
+    name: String,
+    ttl_seconds: Option<u64>,
+    #[serde(default)]
+    expired: webhook::Expired,
+}
+
+fn default_load_minutes() -> u8 {
+    5
+}
+
+fn default_tls_port() -> u16 {
+    443
+}
+
+fn default_warning_days() -> i32 {
+    30
+}
+
+fn default_critical_days() -> i32 {
+    7
+}
+
+fn default_connect_timeout_millis() -> u64 {
+    3000
+}
+
+fn default_timed_out_output() -> actor::OutputConfig {
+    actor::OutputConfig::Named(actor::NamedOutput::TransientError)
+}
+
+fn default_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_empty_output() -> actor::OutputConfig {
+    actor::OutputConfig::Named(actor::NamedOutput::Ok)
+}
+
+fn default_non_empty_output() -> actor::OutputConfig {
+    actor::OutputConfig::Named(actor::NamedOutput::NotOk)
+}
+
+fn default_not_ok_output() -> actor::OutputConfig {
+    actor::OutputConfig::Named(actor::NamedOutput::NotOk)
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(try_from = "serde_yaml::Value")]
+pub struct PadConfig {
+    pub pad: layout::PadRef,
+    pub every_seconds: u8,
+    pub handler: HandlerConfig,
+    /// Mapping as written in the configuration.
+    pub source: serde_yaml::Value,
+}
+
+#[derive(serde::Deserialize)]
+struct PadConfigFields {
+    #[serde(flatten)]
+    pad: layout::PadRef,
+    every_seconds: u8,
+    #[serde(flatten)]
+    handler: HandlerConfig,
+}
+
+impl TryFrom<serde_yaml::Value> for PadConfig {
+    type Error = serde_yaml::Error;
+
+    fn try_from(source: serde_yaml::Value) -> Result<Self, Self::Error> {
+        let fields: PadConfigFields = serde_yaml::from_value(source.clone())?;
+        Ok(PadConfig {
+            pad: fields.pad,
+            every_seconds: fields.every_seconds,
+            handler: fields.handler,
+            source,
+        })
+    }
+}
+
+/// Keys of maps with values, which may contain secrets, e.g. `Authorization` header.
+const SECRET_MAPS: [&str; 2] = ["headers", "envs"];
+
+/// Configuration reported by the status API, with values of secret maps hidden.
+fn redacted(source: &serde_yaml::Value) -> serde_json::Value {
+    fn redact(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    match value {
+                        serde_json::Value::Object(secrets)
+                            if SECRET_MAPS.contains(&key.as_str()) =>
+                        {
+                            secrets
+                                .values_mut()
+                                .for_each(|secret| *secret = "***".into());
+                        }
+                        value => redact(value),
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+            _ => {}
+        }
+    }
+    let mut value = serde_json::to_value(source).unwrap_or_default();
+    redact(&mut value);
+    value
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct MidiPorts {
+    pub input: Option<midi_model::PortSelector>,
+    pub output: Option<midi_model::PortSelector>,
+}
+
+impl MidiPorts {
+    /// Input port selector. By default, port name must contain controller name.
+    pub fn input(&self, controller_name: &str) -> midi_model::PortSelector {
+        self.input
+            .clone()
+            .unwrap_or_else(|| midi_model::PortSelector::Contains(controller_name.to_string()))
+    }
+
+    /// Output port selector. By default, port name must contain controller name.
+    pub fn output(&self, controller_name: &str) -> midi_model::PortSelector {
+        self.output
+            .clone()
+            .unwrap_or_else(|| midi_model::PortSelector::Contains(controller_name.to_string()))
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ControllerConfig {
+    pub controller_name: String,
+    #[serde(default)]
+    pub ports: MidiPorts,
+    pub color_palette: Arc<actor::ColorMapping>,
+    #[serde(default)]
+    pub layout: layout::Layout,
+    /// Pads mapped regardless of the active bank.
+    pub mappings: Vec<PadConfig>,
+    #[serde(default = "Vec::new")]
+    pub banks: Vec<BankConfig>,
+}
+
+/// Set of mappings displayed on the controller only when the bank is active.
+#[derive(Debug, serde::Deserialize)]
+pub struct BankConfig {
+    /// Pad activating the bank.
+    #[serde(flatten)]
+    pub selector: layout::PadRef,
+    pub mappings: Vec<PadConfig>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(try_from = "MidiMappingConfig")]
+pub struct MidiMapping {
+    /// Handlers that can be shared by pads, also between different controllers.
+    pub handlers: HashMap<String, HandlerConfig>,
+    pub server: Option<ServerConfig>,
+    pub controllers: Vec<ControllerConfig>,
+}
+
+/// YAML representation of [`MidiMapping`]. Either `controllers` list,
+/// or a single controller defined at the top level, as before multiple controllers were supported.
+#[derive(serde::Deserialize)]
+struct MidiMappingConfig {
+    #[serde(default = "HashMap::new")]
+    handlers: HashMap<String, HandlerConfig>,
+    server: Option<ServerConfig>,
+    controllers: Option<Vec<ControllerConfig>>,
+    controller_name: Option<String>,
+    ports: Option<MidiPorts>,
+    color_palette: Option<Arc<actor::ColorMapping>>,
+    layout: Option<layout::Layout>,
+    mappings: Option<Vec<PadConfig>>,
+    banks: Option<Vec<BankConfig>>,
+}
+
+impl TryFrom<MidiMappingConfig> for MidiMapping {
+    type Error = String;
+
+    fn try_from(value: MidiMappingConfig) -> Result<Self, Self::Error> {
+        let controllers = match (value.controllers, value.controller_name) {
+            (Some(controllers), None) => {
+                if value.ports.is_some()
+                    || value.color_palette.is_some()
+                    || value.layout.is_some()
+                    || value.mappings.is_some()
+                    || value.banks.is_some()
+                {
+                    return Err(String::from(
+                        "Controller settings must be defined within the controllers list.",
+                    ));
+                }
+                controllers
+            }
+            (None, Some(controller_name)) => vec![ControllerConfig {
+                controller_name,
+                ports: value.ports.unwrap_or_default(),
+                color_palette: value
+                    .color_palette
+                    .ok_or_else(|| String::from("Missing color_palette of the controller."))?,
+                layout: value.layout.unwrap_or_default(),
+                mappings: value
+                    .mappings
+                    .ok_or_else(|| String::from("Missing mappings of the controller."))?,
+                banks: value.banks.unwrap_or_default(),
+            }],
+            (Some(_), Some(_)) => {
+                return Err(String::from(
+                    "Expected either controllers list or a single controller_name, not both.",
+                ))
+            }
+            (None, None) => {
+                return Err(String::from(
+                    "Expected controllers list or a single controller_name.",
+                ))
+            }
+        };
+
+        Ok(MidiMapping {
+            handlers: value.handlers,
+            server: value.server,
+            controllers,
+        })
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ServerConfig {
+    /// Address of the embedded HTTP server, e.g. `127.0.0.1:8080`.
+    pub listen: SocketAddr,
+}
+
+impl MidiMapping {
+    pub fn controller(&self, controller_name: &str) -> Option<&ControllerConfig> {
+        self.controllers
+            .iter()
+            .find(|c| c.controller_name == controller_name)
+    }
+}
+
+// Parsed part - from configuration to application specific
+
+pub enum ParsedHandlerConfig {
+    K8S(k8s_handler::Config),
+    K8SNamespace(k8s_handler::NamespaceConfig),
+    K8SEvents(k8s_handler::EventsConfig),
+    K8SCluster(k8s_handler::ClusterConfig),
+    Script(script_handler::Config),
+    Prometheus(Box<prometheus_handler::Config>),
+    Alertmanager(Box<alertmanager_handler::Config>),
+    Tcp(Box<tcp_handler::Config>),
+    Certificate(Box<certificate_handler::Config>),
+    System(system_handler::Config),
+    File(Box<file_handler::Config>),
+    Composite {
+        policy: composite::Policy,
+        children: Vec<ParsedHandlerConfig>,
+    },
+}
+
+/// Base URL with trailing slash, so that API paths can be joined to it.
+fn base_url(url: &str) -> anyhow::Result<reqwest::Url> {
+    let with_slash = format!("{}/", url.trim_end_matches('/'));
+    reqwest::Url::parse(&with_slash).map_err(|e| anyhow::anyhow!("Invalid URL '{url}': {e}"))
+}
+
+fn header_map(headers: HashMap<String, String>) -> anyhow::Result<reqwest::header::HeaderMap> {
+    let mut header_map = reqwest::header::HeaderMap::new();
+    for (name, value) in headers {
+        header_map.insert(
+            reqwest::header::HeaderName::try_from(name.as_str())?,
+            reqwest::header::HeaderValue::try_from(value)?,
+        );
+    }
+    Ok(header_map)
+}
+
+impl TryFrom<AlertmanagerConfig> for alertmanager_handler::Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AlertmanagerConfig) -> Result<Self, Self::Error> {
+        let matchers = value
+            .matchers
+            .iter()
+            .map(|matcher| alertmanager_handler::Matcher::try_from(matcher.as_str()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if value.silence_minutes.is_some() && matchers.is_empty() {
+            anyhow::bail!("Alertmanager handler requires matchers to create silences.");
+        }
+
+        Ok(alertmanager_handler::Config {
+            url: base_url(&value.url)?,
+            matchers,
+            headers: header_map(value.headers)?,
+            timeout: Duration::from_secs(value.timeout_seconds),
+            severities: value
+                .severities
+                .into_iter()
+                .map(|severity| alertmanager_handler::Severity {
+                    name: severity.severity,
+                    output: severity.output.into(),
+                })
+                .collect(),
+            other_severities: value.other_severities.into(),
+            silence_for: value
+                .silence_minutes
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+        })
+    }
+}
+
+impl TryFrom<K8SConfig> for k8s_handler::Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: K8SConfig) -> Result<Self, Self::Error> {
+        // Deployments are degraded only when asked for, by the threshold or by the color.
+        let replicas = k8s_model::ReplicaThresholds {
+            degraded_below: value
+                .replicas
+                .degraded_below
+                .or(value.degraded.map(|_| 1.0)),
+            ..value.replicas
+        };
+        if !(0.0..=1.0).contains(&replicas.not_ok_below)
+            || !replicas
+                .degraded_below
+                .is_none_or(|below| (replicas.not_ok_below..=1.0).contains(&below))
+        {
+            anyhow::bail!(
+                "Replica thresholds of {} must satisfy 0 <= not_ok_below <= degraded_below <= 1.",
+                value.deployment_id.deployment.0
+            );
+        }
+        if value.watch && value.check_pods {
+            anyhow::bail!(
+                "Pods of {} can't be checked, when the deployment is watched.",
+                value.deployment_id.deployment.0
+            );
+        }
+
+        Ok(k8s_handler::Config {
+            deployment_id: value.deployment_id,
+            replicas,
+            unknown: value.unknown,
+            depl_in_progress: value.depl_in_progress,
+            degraded: value.degraded,
+            check_pods: value.check_pods,
+            crashing: value.crashing,
+        })
+    }
+}
+
+impl TryFrom<TcpConfig> for tcp_handler::Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: TcpConfig) -> Result<Self, Self::Error> {
+        let tls = if value.tls {
+            let connector = native_tls::TlsConnector::builder()
+                .danger_accept_invalid_certs(value.accept_invalid_certs)
+                .build()?;
+            Some(tcp_handler::Tls {
+                connector: connector.into(),
+                server_name: value.tls_server_name.unwrap_or_else(|| value.host.clone()),
+            })
+        } else {
+            None
+        };
+
+        Ok(tcp_handler::Config {
+            host: value.host,
+            port: value.port,
+            timeout: Duration::from_millis(value.timeout_millis),
+            tls,
+            refused: value.refused.into(),
+            handshake_failed: value.handshake_failed.into(),
+            timed_out: value.timed_out.into(),
+        })
+    }
+}
+
+impl TryFrom<CertificateConfig> for certificate_handler::Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: CertificateConfig) -> Result<Self, Self::Error> {
+        if value.critical_days > value.warning_days {
+            anyhow::bail!("Certificate critical_days must not be greater than warning_days.");
+        }
+
+        let source = match value.source {
+            CertificateSourceConfig::File { file } => certificate_handler::Source::File(file),
+            CertificateSourceConfig::Endpoint {
+                host,
+                port,
+                server_name,
+            } => {
+                let connector = native_tls::TlsConnector::builder()
+                    .danger_accept_invalid_certs(true)
+                    .build()?;
+                certificate_handler::Source::Endpoint {
+                    server_name: server_name.unwrap_or_else(|| host.clone()),
+                    host,
+                    port,
+                    connector: connector.into(),
+                }
+            }
+        };
+
+        Ok(certificate_handler::Config {
+            source,
+            timeout: Duration::from_millis(value.timeout_millis),
+            warning_days: value.warning_days,
+            critical_days: value.critical_days,
+            warning: value.warning.into(),
+        })
+    }
+}
+
+/// Thresholds ordered from the highest one.
+fn thresholds(config: Vec<ThresholdConfig>) -> Vec<prometheus_handler::Threshold> {
+    let mut thresholds: Vec<prometheus_handler::Threshold> = config
+        .into_iter()
+        .map(|threshold| prometheus_handler::Threshold {
+            above: threshold.above,
+            output: threshold.output.into(),
+        })
+        .collect();
+    thresholds.sort_by(|a, b| b.above.total_cmp(&a.above));
+    thresholds
+}
+
+impl TryFrom<PrometheusConfig> for prometheus_handler::Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: PrometheusConfig) -> Result<Self, Self::Error> {
+        let url = base_url(&value.url)?.join("api/v1/query")?;
+        let headers = header_map(value.headers)?;
+
+        Ok(prometheus_handler::Config {
+            url,
+            query: value.query,
+            headers,
+            timeout: Duration::from_secs(value.timeout_seconds),
+            empty: value.empty.into(),
+            non_empty: value.non_empty.into(),
+            thresholds: thresholds(value.thresholds),
+        })
+    }
+}
+
+impl TryFrom<SystemConfig> for system_handler::Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: SystemConfig) -> Result<Self, Self::Error> {
+        let (metric, config) = match value {
+            SystemConfig::Disk { mount, thresholds } => {
+                (system_handler::Metric::Disk(mount), thresholds)
+            }
+            SystemConfig::Memory { thresholds } => (system_handler::Metric::Memory, thresholds),
+            SystemConfig::Load {
+                minutes,
+                per_cpu,
+                thresholds,
+            } => {
+                let period = match minutes {
+                    1 => system_handler::LoadPeriod::OneMinute,
+                    5 => system_handler::LoadPeriod::FiveMinutes,
+                    15 => system_handler::LoadPeriod::FifteenMinutes,
+                    _ => anyhow::bail!(
+                        "Load average is available for 1, 5 or 15 minutes, not {minutes}."
+                    ),
+                };
+                (system_handler::Metric::Load { period, per_cpu }, thresholds)
+            }
+            SystemConfig::Process { name, missing } => {
+                return Ok(system_handler::Config::Process {
+                    name,
+                    missing: missing.into(),
+                })
+            }
+        };
+        if config.is_empty() {
+            anyhow::bail!("System check requires at least one threshold.");
+        }
+
+        Ok(system_handler::Config::Usage {
+            metric,
+            thresholds: thresholds(config),
+        })
+    }
+}
+
+impl TryFrom<FileConfig> for file_handler::Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FileConfig) -> Result<Self, Self::Error> {
+        glob::Pattern::new(&value.path)
+            .map_err(|e| anyhow::anyhow!("Invalid file pattern '{}': {e}", value.path))?;
+        let contains = value
+            .contains
+            .map(|contains| regex::Regex::new(&contains))
+            .transpose()?;
+
+        Ok(file_handler::Config {
+            pattern: value.path,
+            max_age: value
+                .max_age_minutes
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+            contains,
+            missing: value.missing.into(),
+            stale: value.stale.into(),
+            matched: value.matched.into(),
+        })
+    }
+}
+
+impl TryFrom<LogTailConfig> for log_tail_handler::Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: LogTailConfig) -> Result<Self, Self::Error> {
+        Ok(log_tail_handler::Config {
+            path: value.path,
+            pattern: regex::Regex::new(&value.pattern)?,
+            window: Duration::from_secs(value.window_seconds),
+            threshold: value.threshold,
+            quiet: Duration::from_secs(value.quiet_seconds.unwrap_or(value.window_seconds)),
+            matched: value.matched.into(),
+        })
+    }
+}
+
+impl TryFrom<HandlerConfig> for ParsedHandlerConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(value: HandlerConfig) -> Result<Self, Self::Error> {
+        match value {
+            HandlerConfig::K8S(config) if config.watch => Err(anyhow::anyhow!(
+                "K8S handler of {} with watch can be referred only directly by the mapping.",
+                config.deployment_id.deployment.0
+            )),
+            HandlerConfig::K8S(config) => Ok(ParsedHandlerConfig::K8S(config.try_into()?)),
+            HandlerConfig::Webhook(config) => Err(anyhow::anyhow!(
+                "Webhook handler '{}' can be referred only directly by the mapping.",
+                config.name
+            )),
+            HandlerConfig::K8SNamespace {
+                namespace_id,
+                label_selector,
+                max_unhealthy,
+                depl_in_progress,
+                unknown,
+            } => Ok(ParsedHandlerConfig::K8SNamespace(
+                k8s_handler::NamespaceConfig {
+                    namespace_id,
+                    label_selector,
+                    max_unhealthy,
+                    unknown,
+                    depl_in_progress,
+                },
+            )),
+            HandlerConfig::K8SEvents {
+                workload_id,
+                reasons,
+                window_seconds,
+                threshold,
+            } => Ok(ParsedHandlerConfig::K8SEvents(k8s_handler::EventsConfig {
+                workload_id,
+                reasons,
+                window: Duration::from_secs(window_seconds),
+                threshold,
+            })),
+            HandlerConfig::K8SNodes { context, degraded } => Ok(ParsedHandlerConfig::K8SCluster(
+                k8s_handler::ClusterConfig {
+                    context,
+                    check: k8s_handler::ClusterCheck::Nodes,
+                    degraded,
+                },
+            )),
+            HandlerConfig::K8SApiServer { context, degraded } => Ok(
+                ParsedHandlerConfig::K8SCluster(k8s_handler::ClusterConfig {
+                    context,
+                    check: k8s_handler::ClusterCheck::ApiServer,
+                    degraded,
+                }),
+            ),
+            HandlerConfig::Script {
+                envs,
+                command,
+                args,
+            } => Ok(ParsedHandlerConfig::Script(script_handler::Config {
+                envs,
+                command,
+                args,
+            })),
+            HandlerConfig::Prometheus(config) => Ok(ParsedHandlerConfig::Prometheus(Box::new(
+                config.try_into()?,
+            ))),
+            HandlerConfig::Alertmanager(config) => Ok(ParsedHandlerConfig::Alertmanager(Box::new(
+                config.try_into()?,
+            ))),
+            HandlerConfig::Tcp(config) => {
+                Ok(ParsedHandlerConfig::Tcp(Box::new(config.try_into()?)))
+            }
+            HandlerConfig::Certificate(config) => Ok(ParsedHandlerConfig::Certificate(Box::new(
+                config.try_into()?,
+            ))),
+            HandlerConfig::System(config) => Ok(ParsedHandlerConfig::System(config.try_into()?)),
+            HandlerConfig::LogTail(config) => Err(anyhow::anyhow!(
+                "LogTail handler of {} can be referred only directly by the mapping.",
+                config.path.display()
+            )),
+            HandlerConfig::File(config) if config.watch => Err(anyhow::anyhow!(
+                "File handler of {} with watch can be referred only directly by the mapping.",
+                config.path
+            )),
+            HandlerConfig::File(config) => {
+                Ok(ParsedHandlerConfig::File(Box::new(config.try_into()?)))
+            }
+            HandlerConfig::Composite { children, .. } if children.is_empty() => Err(
+                anyhow::anyhow!("Composite handler requires at least one child."),
+            ),
+            HandlerConfig::Composite { policy, children } => Ok(ParsedHandlerConfig::Composite {
+                policy,
+                children: children
+                    .into_iter()
+                    .map(ParsedHandlerConfig::try_from)
+                    .collect::<anyhow::Result<_>>()?,
+            }),
+            HandlerConfig::Shared { handler } => Err(anyhow::anyhow!(
+                "Shared handler '{handler}' can be referred only directly by the mapping."
+            )),
+        }
+    }
+}
+
+pub enum PadHandlerConfig {
+    Own(ParsedHandlerConfig),
+    Shared(String),
+    Source(ParsedSourceConfig),
+}
+
+/// Event source, which can't be shared or combined with other handlers.
+pub enum ParsedSourceConfig {
+    K8S(k8s_handler::Config),
+    Webhook(webhook::Config),
+    File(Box<file_handler::Config>),
+    LogTail(Box<log_tail_handler::Config>),
+}
+
+pub struct ParsedPadConfig {
+    pub actor_config: actor::Config,
+    pub handler_config: PadHandlerConfig,
+    /// Type of the handler in the configuration, reported by the status API.
+    pub handler_type: &'static str,
+    /// Mapping reported by the status API, without secrets.
+    pub configuration: serde_json::Value,
+}
+
+pub struct ParsedBank {
+    pub selector: actor::PadId,
+    pub pad_configs: Vec<ParsedPadConfig>,
+}
+
+pub struct ParsedController {
+    pub controller_name: String,
+    pub input_port: midi_model::PortSelector,
+    pub output_port: midi_model::PortSelector,
+    pub color_mapping: Arc<actor::ColorMapping>,
+    pub pad_configs: Vec<ParsedPadConfig>,
+    pub banks: Vec<ParsedBank>,
+}
+
+pub struct ParsedSharedHandler {
+    pub handler_config: ParsedHandlerConfig,
+    /// Result of the handler is reused by all pads for that long.
+    pub fresh_for: Duration,
+}
+
+pub struct ParsedConfig {
+    pub shared_handlers: HashMap<String, ParsedSharedHandler>,
+    pub controllers: Vec<ParsedController>,
+    pub server: Option<ServerConfig>,
+}
+
+struct MappingsParser<'a> {
+    handlers: &'a HashMap<String, HandlerConfig>,
+    /// Shortest interval of pads referring each shared handler.
+    shared_intervals: HashMap<String, Duration>,
+    /// Whether any pad is a webhook.
+    webhooks: bool,
+}
+
+impl<'a> MappingsParser<'a> {
+    fn parse_controller(
+        &mut self,
+        controller: ControllerConfig,
+    ) -> anyhow::Result<ParsedController> {
+        let controller_name = controller.controller_name;
+        let color_mapping = controller.color_palette;
+        let layout = layout::ResolvedLayout::resolve(controller.layout)?;
+
+        // Pads that can't be mapped in any bank.
+        let mut reserved = HashSet::new();
+        let pad_configs = self.parse_mappings(
+            controller.mappings,
+            &layout,
+            &color_mapping,
+            &mut reserved,
+            &controller_name,
+            None,
+        )?;
+
+        let mut bank_mappings = Vec::with_capacity(controller.banks.len());
+        for bank in controller.banks {
+            let selector = layout.pad_id(bank.selector)?;
+            Self::reserve(&mut reserved, &selector, &controller_name)?;
+            bank_mappings.push((selector, bank.mappings));
+        }
+
+        let mut banks = Vec::with_capacity(bank_mappings.len());
+        for (bank_idx, (selector, mappings)) in bank_mappings.into_iter().enumerate() {
+            // The same pad can be mapped in many banks, but only once within the bank.
+            let pad_configs = self.parse_mappings(
+                mappings,
+                &layout,
+                &color_mapping,
+                &mut reserved.clone(),
+                &controller_name,
+                Some(bank_idx),
+            )?;
+            banks.push(ParsedBank {
+                selector,
+                pad_configs,
+            });
+        }
+
+        Ok(ParsedController {
+            input_port: controller.ports.input(&controller_name),
+            output_port: controller.ports.output(&controller_name),
+            controller_name,
+            color_mapping,
+            pad_configs,
+            banks,
+        })
+    }
+
+    fn parse_mappings(
+        &mut self,
+        mappings: Vec<PadConfig>,
+        layout: &layout::ResolvedLayout,
+        color_mapping: &Arc<actor::ColorMapping>,
+        used_pads: &mut HashSet<actor::PadId>,
+        controller_name: &str,
+        bank: Option<usize>,
+    ) -> anyhow::Result<Vec<ParsedPadConfig>> {
+        let mut pad_configs = Vec::with_capacity(mappings.len());
+        for config in mappings {
+            let pad_id = layout.pad_id(config.pad)?;
+            Self::reserve(used_pads, &pad_id, controller_name)?;
+
+            let schedule_every = Duration::from_secs(config.every_seconds.into());
+            let handler_type = config.handler.type_name();
+            let target = config.handler.target();
+            let configuration = redacted(&config.source);
+            let handler_config = match config.handler {
+                HandlerConfig::Shared { handler } => {
+                    if !self.handlers.contains_key(&handler) {
+                        anyhow::bail!("Handler '{handler}' is not defined in handlers section.");
+                    }
+                    let interval = self
+                        .shared_intervals
+                        .entry(handler.clone())
+                        .or_insert(schedule_every);
+                    *interval = (*interval).min(schedule_every);
+                    PadHandlerConfig::Shared(handler)
+                }
+                HandlerConfig::K8S(config) if config.watch => {
+                    PadHandlerConfig::Source(ParsedSourceConfig::K8S(config.try_into()?))
+                }
+                HandlerConfig::File(config) if config.watch => {
+                    PadHandlerConfig::Source(ParsedSourceConfig::File(Box::new(config.try_into()?)))
+                }
+                HandlerConfig::LogTail(config) => PadHandlerConfig::Source(
+                    ParsedSourceConfig::LogTail(Box::new(config.try_into()?)),
+                ),
+                HandlerConfig::Webhook(config) => {
+                    self.webhooks = true;
+                    PadHandlerConfig::Source(ParsedSourceConfig::Webhook(webhook::Config {
+                        name: config.name,
+                        ttl: config.ttl_seconds.map(Duration::from_secs),
+                        expired: config.expired,
+                    }))
+                }
+                handler => PadHandlerConfig::Own(handler.try_into()?),
+            };
+
+            pad_configs.push(ParsedPadConfig {
+                actor_config: actor::Config {
+                    metrics: metrics::PadMetrics::new(controller_name, bank, &pad_id),
+                    span: tracing::info_span!(
+                        "pad",
+                        controller = controller_name,
+                        bank,
+                        pad = %pad_id,
+                        handler = handler_type,
+                        target
+                    ),
+                    pad_mapping: actor::PadMapping {
+                        pad_id,
+                        color_mapping: color_mapping.clone(),
+                    },
+                    schedule_every,
+                    observer: None,
+                },
+                handler_config,
+                handler_type,
+                configuration,
+            });
+        }
+        Ok(pad_configs)
+    }
+
+    fn reserve(
+        used_pads: &mut HashSet<actor::PadId>,
+        pad_id: &actor::PadId,
+        controller_name: &str,
+    ) -> anyhow::Result<()> {
+        if !used_pads.insert(pad_id.clone()) {
+            anyhow::bail!(
+                "Pad (status: {:#04X}, fst_data_byte: {:#04X}) is mapped more than once on {controller_name}.",
+                pad_id.status.as_u8(),
+                pad_id.fst_data_byte.as_u8()
+            );
+        }
+        Ok(())
+    }
+}
+
+pub fn parse(midi_mapping: MidiMapping) -> anyhow::Result<ParsedConfig> {
+    let mut parser = MappingsParser {
+        handlers: &midi_mapping.handlers,
+        shared_intervals: HashMap::new(),
+        webhooks: false,
+    };
+
+    let mut controller_names = HashSet::new();
+    let mut controllers = Vec::with_capacity(midi_mapping.controllers.len());
+    for controller in midi_mapping.controllers {
+        if !controller_names.insert(controller.controller_name.clone()) {
+            anyhow::bail!(
+                "Controller '{}' is defined more than once.",
+                controller.controller_name
+            );
+        }
+        controllers.push(parser.parse_controller(controller)?);
+    }
+
+    if parser.webhooks && midi_mapping.server.is_none() {
+        anyhow::bail!("Webhook pads require the server section.");
+    }
+
+    let mut shared_intervals = parser.shared_intervals;
+    let mut shared_handlers = HashMap::new();
+    for (name, handler_config) in midi_mapping.handlers {
+        // Handlers not referred by any pad are not started at all.
+        if let Some(fresh_for) = shared_intervals.remove(&name) {
+            let shared_handler = ParsedSharedHandler {
+                handler_config: handler_config.try_into()?,
+                fresh_for,
+            };
+            shared_handlers.insert(name, shared_handler);
+        }
+    }
+
+    Ok(ParsedConfig {
+        shared_handlers,
+        controllers,
+        server: midi_mapping.server,
+    })
+}
+
+pub fn load_from_yaml<P: AsRef<Path>>(path: P) -> anyhow::Result<MidiMapping> {
+    let conf_file = std::fs::File::open(path)?;
+    // Workaround for merge anchors.
+    // Useful for better file readability.
+    // https://github.com/dtolnay/serde-yaml/issues/317
+    let mut yaml_value: serde_yaml::Value = serde_yaml::from_reader(conf_file)?;
+    yaml_value.apply_merge()?;
+    Ok(serde_yaml::from_value(yaml_value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PALETTE: &str = "{ok: 0x60, action_triggerred: 0x50, transient_error: 0x40, not_ok: 0x30, initial: 0x20, paused: 0x10}";
+
+    fn parse_yaml(yaml: &str) -> anyhow::Result<ParsedConfig> {
+        parse(serde_yaml::from_str(&yaml.replace("PALETTE", PALETTE))?)
+    }
+
+    #[test]
+    fn parses_multiple_controllers_sharing_handler() {
+        let parsed = parse_yaml(
+            r#"
+handlers:
+  redis:
+    type: Script
+    command: "true"
+  unused:
+    type: Script
+    command: "false"
+controllers:
+  - controller_name: DDJ-XP2
+    color_palette: PALETTE
+    mappings:
+      - {status: 0x97, fst_data_byte: 0x00, every_seconds: 30, type: Shared, handler: redis}
+      - {status: 0x97, fst_data_byte: 0x01, every_seconds: 10, type: Script, command: "true"}
+  - controller_name: Launchpad
+    ports:
+      input: {index: 1}
+    color_palette: PALETTE
+    mappings:
+      - {status: 0x97, fst_data_byte: 0x00, every_seconds: 5, type: Shared, handler: redis}
+"#,
+        )
+        .unwrap();
+
+        let names: Vec<&str> = parsed
+            .controllers
+            .iter()
+            .map(|c| c.controller_name.as_str())
+            .collect();
+        assert_eq!(names, ["DDJ-XP2", "Launchpad"]);
+        assert_eq!(parsed.controllers[0].pad_configs.len(), 2);
+        assert_eq!(parsed.controllers[1].pad_configs.len(), 1);
+        assert!(matches!(
+            parsed.controllers[1].input_port,
+            midi_model::PortSelector::Index(1)
+        ));
+
+        // Shared handler is invoked at the shortest interval of pads referring it.
+        assert_eq!(parsed.shared_handlers.len(), 1);
+        assert_eq!(
+            parsed.shared_handlers["redis"].fresh_for,
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn parses_single_controller_at_top_level() {
+        let parsed = parse_yaml(
+            r#"
+controller_name: DDJ-XP2
+color_palette: PALETTE
+mappings:
+  - {status: 0x97, fst_data_byte: 0x00, every_seconds: 10, type: Script, command: "true"}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed.controllers.len(), 1);
+        assert_eq!(parsed.controllers[0].controller_name, "DDJ-XP2");
+        assert!(matches!(
+            parsed.controllers[0].input_port,
+            midi_model::PortSelector::Contains(_)
+        ));
+    }
+
+    #[test]
+    fn default_port_selection_rejects_several_matching_ports() {
+        let ports = ["DDJ-XP2 MIDI 1", "DDJ-XP2 MIDI 2", "Launchpad X"]
+            .map(String::from)
+            .to_vec();
+
+        let error = MidiPorts::default()
+            .input("DDJ-XP2")
+            .select(&ports)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(r#"["DDJ-XP2 MIDI 1", "DDJ-XP2 MIDI 2"]"#));
+        assert_eq!(
+            MidiPorts::default()
+                .output("Launchpad")
+                .select(&ports)
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn rejects_ambiguous_controllers() {
+        let duplicate = r#"
+controllers:
+  - {controller_name: DDJ-XP2, color_palette: PALETTE, mappings: []}
+  - {controller_name: DDJ-XP2, color_palette: PALETTE, mappings: []}
+"#;
+        assert!(parse_yaml(duplicate).is_err());
+
+        let both = r#"
+controller_name: DDJ-XP2
+color_palette: PALETTE
+mappings: []
+controllers: []
+"#;
+        assert!(parse_yaml(both).is_err());
+
+        let pad_mapped_twice = r#"
+controller_name: DDJ-XP2
+color_palette: PALETTE
+mappings:
+  - {status: 0x97, fst_data_byte: 0x00, every_seconds: 10, type: Script, command: "true"}
+  - {status: 0x97, fst_data_byte: 0x00, every_seconds: 10, type: Script, command: "true"}
+"#;
+        assert!(parse_yaml(pad_mapped_twice).is_err());
+    }
+
+    #[test]
+    fn pad_configuration_is_reported_without_secrets() {
+        let parsed = parse_yaml(
+            r#"
+controller_name: DDJ-XP2
+color_palette: PALETTE
+mappings:
+  - status: 0x97
+    fst_data_byte: 0x00
+    every_seconds: 10
+    type: Prometheus
+    url: http://prometheus:9090
+    query: up == 0
+    headers:
+      Authorization: Bearer secret
+"#,
+        )
+        .unwrap();
+
+        let configuration = &parsed.controllers[0].pad_configs[0].configuration;
+        assert_eq!(configuration["url"], "http://prometheus:9090");
+        assert_eq!(configuration["every_seconds"], 10);
+        assert_eq!(configuration["headers"]["Authorization"], "***");
+    }
+
+    #[test]
+    fn prometheus_query_url_keeps_path_prefix() {
+        let query_url = |url: &str| {
+            let yaml = format!("{{url: '{url}', query: up}}");
+            let config: PrometheusConfig = serde_yaml::from_str(&yaml).unwrap();
+            prometheus_handler::Config::try_from(config)
+                .unwrap()
+                .url
+                .to_string()
+        };
+
+        assert_eq!(
+            query_url("http://prometheus:9090"),
+            "http://prometheus:9090/api/v1/query"
+        );
+        assert_eq!(
+            query_url("http://host/prometheus"),
+            "http://host/prometheus/api/v1/query"
+        );
+        assert_eq!(
+            query_url("http://host/prometheus/"),
+            "http://host/prometheus/api/v1/query"
+        );
+    }
+
+    #[test]
+    fn deployment_is_degraded_only_when_configured() {
+        let thresholds = |extra: &str| {
+            let yaml = format!(
+                "{{context: kind, namespace: default, deployment: redis, depl_in_progress: 0x70, unknown: 0x71{extra}}}"
+            );
+            let config: K8SConfig = serde_yaml::from_str(&yaml).unwrap();
+            k8s_handler::Config::try_from(config).map(|config| config.replicas)
+        };
+
+        assert_eq!(thresholds("").unwrap().degraded_below, None);
+        assert_eq!(
+            thresholds(", degraded: 0x72").unwrap().degraded_below,
+            Some(1.0)
+        );
+        assert_eq!(
+            thresholds(", replicas: {degraded_below: 0.5}")
+                .unwrap()
+                .degraded_below,
+            Some(0.5)
+        );
+        assert!(thresholds(", replicas: {degraded_below: 0.2, not_ok_below: 0.5}").is_err());
+    }
+}
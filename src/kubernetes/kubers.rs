@@ -1,18 +1,54 @@
+use std::path::PathBuf;
+
 use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 
-use k8s_openapi::api::apps::v1::{Deployment, DeploymentCondition};
-use kube_client::config::KubeConfigOptions;
+use k8s_openapi::api::apps::v1::{Deployment, DeploymentCondition, ReplicaSet, StatefulSet};
+use k8s_openapi::api::core::v1::{Event, Node, Pod};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use k8s_openapi::chrono::Utc;
+use kube::runtime::watcher;
+use kube_client::api::ListParams;
+use kube_client::config::{KubeConfigOptions, Kubeconfig};
 use kube_client::{Api, Client, Config};
 
-use crate::kubernetes::model::DeploymentStatus::{InProgress, NonOK, Unknown, OK};
-use crate::kubernetes::model::{DeploymentId, DeploymentStatus, K8sClient};
+use crate::kubernetes::model::DeploymentStatus::{Degraded, InProgress, NonOK, Unknown, OK};
+use crate::kubernetes::model::{
+    ClusterContext, ClusterHealth, ContainerHealth, DeploymentId, DeploymentStatus, K8sClient,
+    NamespaceId, NamespaceStatus, ReplicaThresholds, WarningEvent,
+};
 
-pub struct KubeRsBased;
+#[derive(Default)]
+pub struct KubeRsBased {
+    /// Kubeconfig file used instead of the default one (`KUBECONFIG` or `~/.kube/config`).
+    pub kubeconfig: Option<PathBuf>,
+}
 
 const AVAILABLE: &str = "Available";
 const PROGRESSING: &str = "Progressing";
 const REPLICA_FAILURE: &str = "ReplicaFailure";
 
+/// Reasons of waiting container, which won't recover without intervention.
+const FAILED_WAITING_REASONS: [&str; 5] = [
+    "CrashLoopBackOff",
+    "ImagePullBackOff",
+    "ErrImagePull",
+    "CreateContainerConfigError",
+    "InvalidImageName",
+];
+
+const OOM_KILLED: &str = "OOMKilled";
+
+const READY: &str = "Ready";
+/// Node conditions, which signal problems when true.
+const NODE_PROBLEMS: [&str; 4] = [
+    "MemoryPressure",
+    "DiskPressure",
+    "PIDPressure",
+    "NetworkUnavailable",
+];
+
 const TRUE_COND_STATUS: &str = "True";
 const FALSE_COND_STATUS: &str = "False";
 
@@ -42,7 +78,19 @@ impl DeploymentConditionsSummary {
         }
     }
 
-    fn status(&self) -> DeploymentStatus {
+    fn status(
+        &self,
+        replicas: &ReplicasSummary,
+        thresholds: &ReplicaThresholds,
+    ) -> DeploymentStatus {
+        match self.conditions_status() {
+            OK if replicas.rolling_out() => InProgress,
+            OK => replicas.status(thresholds),
+            status => status,
+        }
+    }
+
+    fn conditions_status(&self) -> DeploymentStatus {
         match (self.available, self.progressing, self.replica_failure) {
             (_, _, Some(true)) => NonOK, // Failure happened. Indicate it immediately.
             (Some(true), Some(true), _) => OK, // Deployment is available. Indicate it immediately.
@@ -53,29 +101,640 @@ impl DeploymentConditionsSummary {
     }
 }
 
+struct ReplicasSummary {
+    desired: i32,
+    ready: i32,
+    updated: i32,
+    unavailable: i32,
+}
+
+impl ReplicasSummary {
+    fn new(deployment: &Deployment) -> ReplicasSummary {
+        let status = deployment.status.as_ref();
+        ReplicasSummary {
+            desired: deployment
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.replicas)
+                .unwrap_or(1),
+            ready: status.and_then(|s| s.ready_replicas).unwrap_or(0),
+            updated: status.and_then(|s| s.updated_replicas).unwrap_or(0),
+            unavailable: status.and_then(|s| s.unavailable_replicas).unwrap_or(0),
+        }
+    }
+
+    fn rolling_out(&self) -> bool {
+        self.updated < self.desired
+    }
+
+    fn status(&self, thresholds: &ReplicaThresholds) -> DeploymentStatus {
+        if self.desired <= 0 {
+            return OK; // Deployment is scaled down on purpose.
+        }
+
+        let ready = self.ready.min(self.desired - self.unavailable);
+        let ratio = f64::from(ready) / f64::from(self.desired);
+        if ready <= 0 || ratio < thresholds.not_ok_below {
+            NonOK
+        } else if thresholds
+            .degraded_below
+            .is_some_and(|degraded_below| ratio < degraded_below)
+        {
+            Degraded
+        } else {
+            OK
+        }
+    }
+}
+
+fn deployment_status(deployment: &Deployment, thresholds: &ReplicaThresholds) -> DeploymentStatus {
+    let mut deployment_state: DeploymentConditionsSummary = Default::default();
+    if let Some(conds) = deployment
+        .status
+        .as_ref()
+        .and_then(|s| s.conditions.as_ref())
+    {
+        conds.iter().for_each(|cond| deployment_state.merge(cond))
+    }
+    deployment_state.status(&ReplicasSummary::new(deployment), thresholds)
+}
+
+fn stateful_set_status(stateful_set: &StatefulSet) -> DeploymentStatus {
+    let desired = stateful_set
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.replicas)
+        .unwrap_or(1);
+    let Some(status) = stateful_set.status.as_ref() else {
+        return Unknown;
+    };
+
+    let ready = status.ready_replicas.unwrap_or(0);
+    let rolling_out = (status.update_revision.is_some()
+        && status.update_revision != status.current_revision)
+        || status.observed_generation < stateful_set.metadata.generation;
+
+    match (ready >= desired, rolling_out) {
+        (true, false) => OK,
+        (_, true) => InProgress,
+        (false, false) => NonOK,
+    }
+}
+
+/// Pod is managed by a Deployment, through its ReplicaSet, or by a StatefulSet.
+/// Such pods are reflected by the status of their workload.
+fn owned_by_workload(pod: &Pod) -> bool {
+    pod.metadata
+        .owner_references
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .any(|owner| {
+            owner.controller == Some(true)
+                && matches!(owner.kind.as_str(), "ReplicaSet" | "StatefulSet")
+        })
+}
+
+fn pod_status(pod: &Pod) -> DeploymentStatus {
+    let Some(status) = pod.status.as_ref() else {
+        return Unknown;
+    };
+
+    let containers = status.container_statuses.as_deref().unwrap_or_default();
+    let failed_container = containers.iter().any(|container| {
+        container
+            .state
+            .as_ref()
+            .and_then(|state| state.waiting.as_ref())
+            .and_then(|waiting| waiting.reason.as_deref())
+            .is_some_and(|reason| FAILED_WAITING_REASONS.contains(&reason))
+    });
+    let all_ready = containers.iter().all(|container| container.ready);
+
+    match (status.phase.as_deref(), failed_container, all_ready) {
+        (Some("Succeeded"), _, _) => OK,
+        (Some("Failed"), _, _) | (_, true, _) => NonOK,
+        (Some("Running"), false, true) => OK,
+        (Some("Running") | Some("Pending"), false, _) => InProgress,
+        _ => Unknown,
+    }
+}
+
+fn container_health(pod: &Pod) -> Vec<ContainerHealth> {
+    let pod_name = pod.metadata.name.as_deref().unwrap_or_default();
+    let containers = pod
+        .status
+        .as_ref()
+        .and_then(|status| status.container_statuses.as_deref())
+        .unwrap_or_default();
+
+    containers
+        .iter()
+        .map(|container| {
+            let failure = container
+                .state
+                .as_ref()
+                .and_then(|state| state.waiting.as_ref())
+                .and_then(|waiting| waiting.reason.as_deref())
+                .filter(|reason| FAILED_WAITING_REASONS.contains(reason));
+            // Killed container is restarted, so the reason is kept only in its last state.
+            let oom_killed = container
+                .last_state
+                .as_ref()
+                .and_then(|state| state.terminated.as_ref())
+                .and_then(|terminated| terminated.reason.as_deref())
+                .is_some_and(|reason| reason == OOM_KILLED);
+
+            ContainerHealth {
+                id: format!("{pod_name}/{}", container.name),
+                restart_count: u32::try_from(container.restart_count).unwrap_or_default(),
+                failure: failure.map(String::from),
+                oom_killed,
+            }
+        })
+        .collect()
+}
+
+/// Label selector of the deployment's pods.
+fn pod_selector(deployment: &Deployment) -> anyhow::Result<String> {
+    deployment
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.selector.match_labels.as_ref())
+        .filter(|labels| !labels.is_empty())
+        .map(|labels| {
+            labels
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Deployment {} doesn't select pods by labels.",
+                deployment.metadata.name.as_deref().unwrap_or_default()
+            )
+        })
+}
+
+fn controlled_by(object: &ObjectMeta, kind: &str, name: &str) -> bool {
+    object
+        .owner_references
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .any(|owner| owner.controller == Some(true) && owner.kind == kind && owner.name == name)
+}
+
+fn warning_event(event: &Event) -> WarningEvent {
+    let series = event.series.as_ref();
+    let count = series
+        .and_then(|series| series.count)
+        .or(event.count)
+        .unwrap_or(1);
+    let last_seen = series
+        .and_then(|series| series.last_observed_time.as_ref())
+        .map(|time| time.0)
+        .or_else(|| event.last_timestamp.as_ref().map(|time| time.0))
+        .or_else(|| event.event_time.as_ref().map(|time| time.0));
+
+    WarningEvent {
+        uid: event
+            .metadata
+            .uid
+            .clone()
+            .or_else(|| event.metadata.name.clone())
+            .unwrap_or_default(),
+        reason: event.reason.clone().unwrap_or_default(),
+        count: u32::try_from(count).unwrap_or_default(),
+        age: last_seen.and_then(|time| (Utc::now() - time).to_std().ok()),
+    }
+}
+
+/// Returns whether the node is ready, and whether it has no problems.
+fn node_conditions(node: &Node) -> (bool, bool) {
+    let conditions = node
+        .status
+        .as_ref()
+        .and_then(|status| status.conditions.as_deref())
+        .unwrap_or_default();
+
+    let ready = conditions
+        .iter()
+        .any(|cond| cond.type_ == READY && cond.status == TRUE_COND_STATUS);
+    let no_problems = !conditions.iter().any(|cond| {
+        NODE_PROBLEMS.contains(&cond.type_.as_str()) && cond.status == TRUE_COND_STATUS
+    });
+    (ready, no_problems)
+}
+
+impl KubeRsBased {
+    async fn client(&self, context: &ClusterContext) -> anyhow::Result<Client> {
+        let context_options = KubeConfigOptions {
+            context: Some(context.0.clone()),
+            ..Default::default()
+        };
+        let config = match &self.kubeconfig {
+            Some(path) => {
+                Config::from_custom_kubeconfig(Kubeconfig::read_from(path)?, &context_options)
+                    .await?
+            }
+            None => Config::from_kubeconfig(&context_options).await?,
+        };
+        Ok(Client::try_from(config)?)
+    }
+}
+
 #[async_trait]
 impl K8sClient for KubeRsBased {
     async fn check_deployment(
         &self,
         deployment_id: &DeploymentId,
+        thresholds: &ReplicaThresholds,
     ) -> anyhow::Result<DeploymentStatus> {
-        let context_options = KubeConfigOptions {
-            context: Some(deployment_id.context.0.clone()),
-            ..Default::default()
-        };
-        let config = Config::from_kubeconfig(&context_options).await?;
-        let client = Client::try_from(config)?;
+        let client = self.client(&deployment_id.context).await?;
 
         let deployment: Deployment = Api::namespaced(client, deployment_id.namespace.0.as_str())
             .get(deployment_id.deployment.0.as_str())
             .await?;
 
-        let mut deployment_state: DeploymentConditionsSummary = Default::default();
-        if let Some(conds) = deployment.status.and_then(|status| status.conditions) {
-            conds
-                .into_iter()
-                .for_each(|cond| deployment_state.merge(&cond))
+        Ok(deployment_status(&deployment, thresholds))
+    }
+
+    async fn watch_deployment(
+        &self,
+        deployment_id: &DeploymentId,
+        thresholds: &ReplicaThresholds,
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<DeploymentStatus>>> {
+        let client = self.client(&deployment_id.context).await?;
+        let api: Api<Deployment> = Api::namespaced(client, deployment_id.namespace.0.as_str());
+        let list_params =
+            ListParams::default().fields(&format!("metadata.name={}", deployment_id.deployment.0));
+        let thresholds = *thresholds;
+
+        // Missing deployment is not OK, as it won't recover by itself.
+        let statuses = watcher(api, list_params).map(move |event| match event? {
+            watcher::Event::Applied(deployment) => Ok(deployment_status(&deployment, &thresholds)),
+            watcher::Event::Restarted(deployments) => {
+                Ok(deployments.first().map_or(NonOK, |deployment| {
+                    deployment_status(deployment, &thresholds)
+                }))
+            }
+            watcher::Event::Deleted(_) => Ok(NonOK),
+        });
+        Ok(statuses.boxed())
+    }
+
+    async fn check_namespace(
+        &self,
+        namespace_id: &NamespaceId,
+        label_selector: Option<&str>,
+    ) -> anyhow::Result<NamespaceStatus> {
+        let client = self.client(&namespace_id.context).await?;
+        let namespace = namespace_id.namespace.0.as_str();
+        let list_params = match label_selector {
+            Some(selector) => ListParams::default().labels(selector),
+            None => ListParams::default(),
+        };
+
+        let deployments = Api::<Deployment>::namespaced(client.clone(), namespace)
+            .list(&list_params)
+            .await?;
+        let stateful_sets = Api::<StatefulSet>::namespaced(client.clone(), namespace)
+            .list(&list_params)
+            .await?;
+        let pods = Api::<Pod>::namespaced(client, namespace)
+            .list(&list_params)
+            .await?;
+
+        let workloads = deployments
+            .iter()
+            .map(|deployment| deployment_status(deployment, &ReplicaThresholds::default()))
+            .chain(stateful_sets.iter().map(stateful_set_status))
+            .chain(
+                pods.iter()
+                    .filter(|pod| !owned_by_workload(pod))
+                    .map(pod_status),
+            )
+            .collect();
+
+        Ok(NamespaceStatus { workloads })
+    }
+
+    async fn deployment_containers(
+        &self,
+        deployment_id: &DeploymentId,
+    ) -> anyhow::Result<Vec<ContainerHealth>> {
+        let client = self.client(&deployment_id.context).await?;
+        let namespace = deployment_id.namespace.0.as_str();
+
+        let deployment: Deployment = Api::namespaced(client.clone(), namespace)
+            .get(deployment_id.deployment.0.as_str())
+            .await?;
+        let label_selector = pod_selector(&deployment)?;
+
+        let pods = Api::<Pod>::namespaced(client, namespace)
+            .list(&ListParams::default().labels(&label_selector))
+            .await?;
+
+        Ok(pods.iter().flat_map(container_health).collect())
+    }
+
+    async fn warning_events(
+        &self,
+        workload_id: &DeploymentId,
+    ) -> anyhow::Result<Vec<WarningEvent>> {
+        let client = self.client(&workload_id.context).await?;
+        let namespace = workload_id.namespace.0.as_str();
+        let workload_name = workload_id.deployment.0.as_str();
+
+        let deployment: Deployment = Api::namespaced(client.clone(), namespace)
+            .get(workload_name)
+            .await?;
+        let selected = ListParams::default().labels(&pod_selector(&deployment)?);
+        let replica_sets = Api::<ReplicaSet>::namespaced(client.clone(), namespace)
+            .list(&selected)
+            .await?;
+        let pods = Api::<Pod>::namespaced(client.clone(), namespace)
+            .list(&selected)
+            .await?;
+
+        // Deployment, its ReplicaSets and their Pods.
+        let mut involved = vec![("Deployment", workload_name)];
+        involved.extend(
+            replica_sets
+                .iter()
+                .filter(|replica_set| {
+                    controlled_by(&replica_set.metadata, "Deployment", workload_name)
+                })
+                .filter_map(|replica_set| replica_set.metadata.name.as_deref())
+                .map(|name| ("ReplicaSet", name)),
+        );
+        involved.extend(
+            pods.iter()
+                .filter_map(|pod| pod.metadata.name.as_deref())
+                .map(|name| ("Pod", name)),
+        );
+
+        let events = Api::<Event>::namespaced(client, namespace)
+            .list(&ListParams::default().fields("type=Warning"))
+            .await?;
+
+        Ok(events
+            .iter()
+            .filter(|event| {
+                let object = &event.involved_object;
+                object
+                    .kind
+                    .as_deref()
+                    .zip(object.name.as_deref())
+                    .is_some_and(|object| involved.contains(&object))
+            })
+            .map(warning_event)
+            .collect())
+    }
+
+    async fn check_nodes(&self, context: &ClusterContext) -> anyhow::Result<ClusterHealth> {
+        let client = self.client(context).await?;
+        let nodes = Api::<Node>::all(client)
+            .list(&ListParams::default())
+            .await?;
+
+        let conditions: Vec<(bool, bool)> = nodes.iter().map(node_conditions).collect();
+        let any_ready = conditions.iter().any(|(ready, _)| *ready);
+        let all_fine = conditions
+            .iter()
+            .all(|(ready, no_problems)| *ready && *no_problems);
+
+        Ok(match (any_ready, all_fine) {
+            (false, _) => ClusterHealth::Down,
+            (true, false) => ClusterHealth::Degraded,
+            (true, true) => ClusterHealth::Healthy,
+        })
+    }
+
+    async fn check_api_server(&self, context: &ClusterContext) -> anyhow::Result<ClusterHealth> {
+        let client = self.client(context).await?;
+        let request = k8s_openapi::http::Request::get("/readyz").body(Vec::new())?;
+
+        Ok(match client.request_text(request).await {
+            Ok(_) => ClusterHealth::Healthy,
+            // API server responded, but some of its checks failed.
+            Err(kube_client::Error::Api(response)) if response.code >= 500 => {
+                ClusterHealth::Degraded
+            }
+            // API server responded, but readiness can't be read, e.g. with expired credentials or when throttled.
+            Err(kube_client::Error::Api(response)) => {
+                tracing::warn!(code = response.code, reason = %response.reason, "Readiness can't be read.");
+                ClusterHealth::Degraded
+            }
+            Err(_) => ClusterHealth::Down,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::api::apps::v1::{DeploymentSpec, DeploymentStatus as K8sDeploymentStatus};
+
+    use super::*;
+
+    struct Replicas {
+        desired: i32,
+        ready: i32,
+        updated: i32,
+        unavailable: i32,
+    }
+
+    const ALL_READY: Replicas = Replicas {
+        desired: 10,
+        ready: 10,
+        updated: 10,
+        unavailable: 0,
+    };
+
+    fn deployment(conditions: &[(&str, &str)], replicas: Replicas) -> Deployment {
+        Deployment {
+            spec: Some(DeploymentSpec {
+                replicas: Some(replicas.desired),
+                ..Default::default()
+            }),
+            status: Some(K8sDeploymentStatus {
+                conditions: Some(
+                    conditions
+                        .iter()
+                        .map(|(type_, status)| DeploymentCondition {
+                            type_: type_.to_string(),
+                            status: status.to_string(),
+                            ..Default::default()
+                        })
+                        .collect(),
+                ),
+                ready_replicas: Some(replicas.ready),
+                updated_replicas: Some(replicas.updated),
+                unavailable_replicas: Some(replicas.unavailable),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn healthy_conditions() -> Vec<(&'static str, &'static str)> {
+        vec![
+            (AVAILABLE, TRUE_COND_STATUS),
+            (PROGRESSING, TRUE_COND_STATUS),
+        ]
+    }
+
+    fn ready(ready: i32) -> Replicas {
+        Replicas {
+            ready,
+            unavailable: 10 - ready,
+            ..ALL_READY
         }
-        Ok(deployment_state.status())
+    }
+
+    const DEGRADED_BELOW_ALL: ReplicaThresholds = ReplicaThresholds {
+        degraded_below: Some(1.0),
+        not_ok_below: 0.0,
+    };
+
+    fn status(conditions: &[(&str, &str)], replicas: Replicas) -> DeploymentStatus {
+        deployment_status(&deployment(conditions, replicas), &DEGRADED_BELOW_ALL)
+    }
+
+    #[test]
+    fn conditions_without_replica_problems() {
+        let cases = [
+            (vec![(REPLICA_FAILURE, TRUE_COND_STATUS)], NonOK),
+            (
+                vec![
+                    (AVAILABLE, TRUE_COND_STATUS),
+                    (PROGRESSING, TRUE_COND_STATUS),
+                    (REPLICA_FAILURE, TRUE_COND_STATUS),
+                ],
+                NonOK,
+            ),
+            (healthy_conditions(), OK),
+            (
+                vec![
+                    (AVAILABLE, TRUE_COND_STATUS),
+                    (PROGRESSING, FALSE_COND_STATUS),
+                ],
+                NonOK,
+            ),
+            (
+                vec![
+                    (AVAILABLE, FALSE_COND_STATUS),
+                    (PROGRESSING, TRUE_COND_STATUS),
+                ],
+                InProgress,
+            ),
+            (vec![(PROGRESSING, TRUE_COND_STATUS)], InProgress),
+            (vec![(AVAILABLE, TRUE_COND_STATUS)], Unknown),
+            (
+                vec![
+                    (AVAILABLE, FALSE_COND_STATUS),
+                    (PROGRESSING, FALSE_COND_STATUS),
+                ],
+                Unknown,
+            ),
+            (
+                vec![(AVAILABLE, "Unknown"), (PROGRESSING, "Unknown")],
+                Unknown,
+            ),
+            (vec![], Unknown),
+        ];
+
+        for (conditions, expected) in cases {
+            assert_eq!(status(&conditions, ALL_READY), expected, "{conditions:?}");
+        }
+    }
+
+    #[test]
+    fn partially_ready_replicas_are_degraded() {
+        assert_eq!(status(&healthy_conditions(), ready(1)), Degraded);
+        assert_eq!(status(&healthy_conditions(), ready(9)), Degraded);
+    }
+
+    #[test]
+    fn no_ready_replicas_are_not_ok() {
+        assert_eq!(status(&healthy_conditions(), ready(0)), NonOK);
+    }
+
+    #[test]
+    fn partially_ready_replicas_are_ok_by_default() {
+        let status = |ready_replicas| {
+            deployment_status(
+                &deployment(&healthy_conditions(), ready(ready_replicas)),
+                &ReplicaThresholds::default(),
+            )
+        };
+
+        assert_eq!(status(9), OK);
+        assert_eq!(status(1), OK);
+        assert_eq!(status(0), NonOK);
+    }
+
+    #[test]
+    fn unavailable_replicas_are_not_counted_as_ready() {
+        let replicas = Replicas {
+            unavailable: 2,
+            ..ALL_READY
+        };
+        assert_eq!(status(&healthy_conditions(), replicas), Degraded);
+    }
+
+    #[test]
+    fn rollout_in_progress_is_not_degraded() {
+        let replicas = Replicas {
+            updated: 5,
+            ..ready(8)
+        };
+        assert_eq!(status(&healthy_conditions(), replicas), InProgress);
+    }
+
+    #[test]
+    fn replica_problems_do_not_hide_failed_conditions() {
+        let conditions = [(REPLICA_FAILURE, TRUE_COND_STATUS)];
+        assert_eq!(status(&conditions, ready(5)), NonOK);
+
+        let conditions = [
+            (AVAILABLE, FALSE_COND_STATUS),
+            (PROGRESSING, TRUE_COND_STATUS),
+        ];
+        assert_eq!(status(&conditions, ready(0)), InProgress);
+    }
+
+    #[test]
+    fn scaled_down_deployment_is_ok() {
+        let replicas = Replicas {
+            desired: 0,
+            ready: 0,
+            updated: 0,
+            unavailable: 0,
+        };
+        assert_eq!(status(&healthy_conditions(), replicas), OK);
+    }
+
+    #[test]
+    fn thresholds_are_configurable() {
+        let thresholds = ReplicaThresholds {
+            degraded_below: Some(0.8),
+            not_ok_below: 0.5,
+        };
+        let status = |ready_replicas| {
+            deployment_status(
+                &deployment(&healthy_conditions(), ready(ready_replicas)),
+                &thresholds,
+            )
+        };
+
+        assert_eq!(status(10), OK);
+        assert_eq!(status(8), OK);
+        assert_eq!(status(7), Degraded);
+        assert_eq!(status(5), Degraded);
+        assert_eq!(status(4), NonOK);
+        assert_eq!(status(0), NonOK);
     }
 }
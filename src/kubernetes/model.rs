@@ -1,3 +1,8 @@
+use std::fmt;
+use std::time::Duration;
+
+use futures::stream::BoxStream;
+
 #[derive(Debug, serde::Deserialize)]
 pub struct ClusterContext(pub String);
 
@@ -14,12 +19,116 @@ pub struct DeploymentId {
     pub deployment: DeploymentName,
 }
 
-#[derive(Debug)]
+#[derive(Debug, serde::Deserialize)]
+pub struct NamespaceId {
+    pub context: ClusterContext,
+    pub namespace: Namespace,
+}
+
+impl fmt::Display for ClusterContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Formatted as `context/namespace/deployment`.
+impl fmt::Display for DeploymentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            self.context, self.namespace.0, self.deployment.0
+        )
+    }
+}
+
+/// Formatted as `context/namespace`.
+impl fmt::Display for NamespaceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.context, self.namespace.0)
+    }
+}
+
+/// Variants are ordered from the least to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DeploymentStatus {
     OK,
     InProgress,
-    NonOK,
     Unknown,
+    /// Only part of desired replicas is ready.
+    Degraded,
+    NonOK,
+}
+
+/// Ratios of ready to desired replicas, below which the deployment is degraded or not OK.
+/// Deployment without any ready replica is always not OK, and is never degraded without `degraded_below`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct ReplicaThresholds {
+    pub degraded_below: Option<f64>,
+    pub not_ok_below: f64,
+}
+
+impl DeploymentStatus {
+    pub fn is_unhealthy(&self) -> bool {
+        *self >= DeploymentStatus::Degraded
+    }
+}
+
+/// Statuses of all workloads in the namespace.
+#[derive(Debug)]
+pub struct NamespaceStatus {
+    pub workloads: Vec<DeploymentStatus>,
+}
+
+impl NamespaceStatus {
+    /// Number of workloads which are degraded or not OK.
+    pub fn unhealthy(&self) -> usize {
+        self.workloads
+            .iter()
+            .filter(|status| status.is_unhealthy())
+            .count()
+    }
+
+    /// The most severe status of workloads, skipping unhealthy ones when `skip_unhealthy` is set.
+    pub fn worst(&self, skip_unhealthy: bool) -> DeploymentStatus {
+        self.workloads
+            .iter()
+            .copied()
+            .filter(|status| !skip_unhealthy || !status.is_unhealthy())
+            .max()
+            .unwrap_or(DeploymentStatus::OK)
+    }
+}
+
+/// Variants are ordered from the least to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ClusterHealth {
+    Healthy,
+    Degraded,
+    Down,
+}
+
+/// Warning event, which has happened `count` times.
+#[derive(Debug)]
+pub struct WarningEvent {
+    pub uid: String,
+    pub reason: String,
+    pub count: u32,
+    /// Time since the latest occurrence, if known.
+    pub age: Option<Duration>,
+}
+
+/// State of a single container of the workload's pod.
+#[derive(Debug)]
+pub struct ContainerHealth {
+    /// Pod and container name, unique within the namespace.
+    pub id: String,
+    pub restart_count: u32,
+    /// Reason of the container failure, which won't recover without intervention, e.g. `CrashLoopBackOff`.
+    pub failure: Option<String>,
+    /// Whether the previous instance of the container was killed for running out of memory.
+    pub oom_killed: bool,
 }
 
 #[async_trait::async_trait]
@@ -27,5 +136,38 @@ pub trait K8sClient {
     async fn check_deployment(
         &self,
         deployment_id: &DeploymentId,
+        thresholds: &ReplicaThresholds,
     ) -> anyhow::Result<DeploymentStatus>;
+
+    /// Watches the deployment. Stream yields its status after every change, and errors after which the watch is restarted.
+    /// Missing or deleted deployment is not OK.
+    async fn watch_deployment(
+        &self,
+        deployment_id: &DeploymentId,
+        thresholds: &ReplicaThresholds,
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<DeploymentStatus>>>;
+
+    /// Checks Deployments, StatefulSets and Pods in the namespace, optionally matching label selector.
+    /// Pods owned by Deployments or StatefulSets are reflected only by their workloads.
+    async fn check_namespace(
+        &self,
+        namespace_id: &NamespaceId,
+        label_selector: Option<&str>,
+    ) -> anyhow::Result<NamespaceStatus>;
+
+    /// Lists containers of pods matching the deployment's selector.
+    async fn deployment_containers(
+        &self,
+        deployment_id: &DeploymentId,
+    ) -> anyhow::Result<Vec<ContainerHealth>>;
+
+    /// Lists warning events of the deployment, of its ReplicaSets and of Pods selected by it.
+    async fn warning_events(&self, workload_id: &DeploymentId)
+        -> anyhow::Result<Vec<WarningEvent>>;
+
+    /// Checks conditions of all nodes in the cluster.
+    async fn check_nodes(&self, context: &ClusterContext) -> anyhow::Result<ClusterHealth>;
+
+    /// Checks readiness of the API server. Unreachable API server is reported as `Down`, not as an error.
+    async fn check_api_server(&self, context: &ClusterContext) -> anyhow::Result<ClusterHealth>;
 }
@@ -2,26 +2,83 @@ extern crate core;
 
 use crate::actor::PadHandler;
 use clap::Parser;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::configuration as conf;
 use crate::midi::controller::midir;
 use crate::midi::model as midi_model;
-use crate::worker::{actor, k8s as k8s_handler, script as script_handler};
+use crate::worker::bank::BankSwitcher;
+use crate::worker::composite::CompositeHandler;
+use crate::worker::shared::SharedHandler;
+use crate::worker::{
+    actor, alertmanager as alertmanager_handler, certificate as certificate_handler,
+    file as file_handler, k8s as k8s_handler, log_tail as log_tail_handler,
+    prometheus as prometheus_handler, script as script_handler, system as system_handler,
+    tcp as tcp_handler, webhook,
+};
 
+mod cli;
 mod configuration;
 mod extension;
 mod kubernetes;
+mod metrics;
 mod midi;
+mod server;
+#[cfg(test)]
+mod testing;
 mod worker;
 
 #[derive(clap::Parser)]
 struct CLIArgs {
-    #[arg(short = 'p', long, default_value = "midi_config.yaml")]
+    #[arg(short = 'p', long, global = true, default_value = "midi_config.yaml")]
     pub config_path: String,
+    /// Logs debug events, or trace events when repeated. Overridden by RUST_LOG.
+    #[arg(short = 'v', long = "verbose", global = true, action = clap::ArgAction::Count)]
+    pub verbosity: u8,
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Builds mappings interactively by pressing pads on the controller.
+    Learn(cli::learn::Args),
+    /// Lists MIDI input and output ports and marks the ones matching the controller.
+    Ports(cli::ports::Args),
+    /// Prints decoded MIDI messages received from the controller.
+    Monitor(cli::monitor::Args),
 }
 
 fn main() -> anyhow::Result<()> {
+    let cli_args = CLIArgs::parse();
+    init_logging(cli_args.verbosity, cli_args.log_format);
+
+    match &cli_args.command {
+        Some(Command::Learn(args)) => {
+            let target = cli_target(&args.controller, &cli_args.config_path)?;
+            return cli::learn::run(args, &target);
+        }
+        Some(Command::Ports(args)) => {
+            let target = cli_target(&args.controller, &cli_args.config_path).ok();
+            return cli::ports::run(target.as_ref());
+        }
+        Some(Command::Monitor(args)) => {
+            let target = cli_target(&args.controller, &cli_args.config_path)?;
+            return cli::monitor::run(&target);
+        }
+        None => {}
+    }
+
     let tokio_runtime = Arc::new(
         tokio::runtime::Builder::new_multi_thread()
             .enable_all()
@@ -29,56 +86,349 @@ fn main() -> anyhow::Result<()> {
     );
 
     let shared_runtime = tokio_runtime.clone();
-    tokio_runtime.block_on(run_program(shared_runtime))
+    tokio_runtime.block_on(run_program(cli_args, shared_runtime))
 }
 
-async fn run_program(tokio_runtime: Arc<tokio::runtime::Runtime>) -> anyhow::Result<()> {
-    let cli_args = CLIArgs::parse();
-
+async fn run_program(
+    cli_args: CLIArgs,
+    tokio_runtime: Arc<tokio::runtime::Runtime>,
+) -> anyhow::Result<()> {
     let parsed_config = load_and_parse(&cli_args.config_path)?;
 
-    let k8s_client = Arc::new(kubernetes::kubers::KubeRsBased);
-    let midi_sender: Arc<dyn midi_model::MidiSender + Send + Sync> = Arc::new(
-        midir::MidirBasedSender::new(&parsed_config.controller_name)?,
-    );
-    let midi_receiver = midir::MidirBasedReceiver::new(&parsed_config.controller_name)?;
+    let ctx = PadContext {
+        k8s_client: Arc::new(kubernetes::kubers::KubeRsBased::default()),
+        runtime: Arc::new(actor::TokioRuntime::new(tokio_runtime)),
+        webhooks: webhook::Webhooks::default(),
+        pads: server::api::Pads::default(),
+    };
 
-    let runtime = Arc::new(actor::TokioRuntime::new(tokio_runtime));
     let mut handles = Vec::new();
-    let (handle, listener_actor) = actor::PadChangesListener::start(midi_receiver, runtime.clone());
-    handles.push(handle);
-
-    for pad_config in parsed_config.pad_configs {
-        let handler: Arc<tokio::sync::Mutex<dyn PadHandler>> = match pad_config.handler_config {
-            conf::ParsedHandlerConfig::K8S(config) => Arc::new(tokio::sync::Mutex::new(
-                k8s_handler::K8SDeploymentHandler::new(k8s_client.clone(), config),
-            )),
-            conf::ParsedHandlerConfig::Script(config) => Arc::new(tokio::sync::Mutex::new(
-                script_handler::ScriptHandler::new(config),
-            )),
-        };
+    start_pads(
+        parsed_config.shared_handlers,
+        parsed_config.controllers,
+        &ctx,
+        &mut handles,
+    )?;
 
-        let (handle, actor) = actor::PadActor::start(
-            handler,
-            midi_sender.clone(),
-            runtime.clone(),
-            pad_config.actor_config,
-        );
+    let Some(server_config) = parsed_config.server else {
+        for handle in handles {
+            let _ = handle.0.await;
+        }
+        return Ok(());
+    };
 
-        listener_actor.register(actor.pad_id.clone(), Arc::new(actor));
+    let (reload_sender, reload_requests) = async_channel::bounded(1);
+    let state = Arc::new(server::ServerState {
+        webhooks: ctx.webhooks.clone(),
+        pads: ctx.pads.clone(),
+        reload: reload_sender,
+    });
+    let (address, _) = server::start(server_config.listen, state)?;
+    tracing::info!("Server listening on {address}.");
 
-        handles.push(handle);
+    // Server keeps running, only the pads are restarted.
+    while let Ok(reply) = reload_requests.recv().await {
+        let reloaded = reload(&cli_args.config_path, &ctx, &mut handles).await;
+        match &reloaded {
+            Ok(()) => tracing::info!("Configuration reloaded."),
+            Err(e) => tracing::error!(error = %e, "Configuration not reloaded."),
+        }
+        let _ = reply.send(reloaded.map_err(|e| format!("{e:#}")));
     }
 
-    for handle in handles {
+    Ok(())
+}
+
+/// Shared by all pads, including the ones started after reload.
+struct PadContext {
+    k8s_client: Arc<kubernetes::kubers::KubeRsBased>,
+    runtime: Arc<actor::TokioRuntime>,
+    webhooks: webhook::Webhooks,
+    pads: server::api::Pads,
+}
+
+/// Replaces all pads with the ones in the configuration.
+/// Running pads are kept, when the configuration is invalid. Server section is not reloaded.
+async fn reload(
+    config_path: &str,
+    ctx: &PadContext,
+    handles: &mut Vec<actor::ActorHandle>,
+) -> anyhow::Result<()> {
+    let parsed_config = load_and_parse(config_path)?;
+
+    stop_all(handles).await;
+    ctx.webhooks.clear();
+    ctx.pads.clear();
+
+    let started = start_pads(
+        parsed_config.shared_handlers,
+        parsed_config.controllers,
+        ctx,
+        handles,
+    );
+    if started.is_err() {
+        // E.g. the controller has been disconnected.
+        stop_all(handles).await;
+        ctx.pads.clear();
+    }
+    started
+}
+
+async fn stop_all(handles: &mut Vec<actor::ActorHandle>) {
+    for handle in handles.iter() {
+        handle.0.abort();
+    }
+    for handle in handles.drain(..) {
         let _ = handle.0.await;
     }
+}
+
+/// Starts the pads of all controllers. Handles of already started actors are kept on error.
+fn start_pads(
+    shared_handlers: HashMap<String, conf::ParsedSharedHandler>,
+    controllers: Vec<conf::ParsedController>,
+    ctx: &PadContext,
+    handles: &mut Vec<actor::ActorHandle>,
+) -> anyhow::Result<()> {
+    let shared_handlers: HashMap<String, Arc<tokio::sync::Mutex<dyn PadHandler>>> = shared_handlers
+        .into_iter()
+        .map(|(name, shared)| {
+            let handler = create_handler(shared.handler_config, &ctx.k8s_client);
+            let shared_handler: Arc<tokio::sync::Mutex<dyn PadHandler>> = Arc::new(
+                tokio::sync::Mutex::new(SharedHandler::new(handler, shared.fresh_for)),
+            );
+            (name, shared_handler)
+        })
+        .collect();
+
+    let listener = actor::PadChangesListener::default();
+    for controller in controllers {
+        let midi_sender: Arc<dyn midi_model::MidiSender + Send + Sync> = Arc::new(
+            midir::MidirBasedSender::new(&controller.controller_name, &controller.output_port)?,
+        );
+        let midi_receiver =
+            midir::MidirBasedReceiver::new(&controller.controller_name, &controller.input_port)?;
+
+        handles.push(listener.start(
+            &controller.controller_name,
+            midi_receiver,
+            ctx.runtime.clone(),
+        ));
+        let scoped = |pad_id: actor::PadId| actor::ScopedPadId {
+            controller: controller.controller_name.clone(),
+            pad_id,
+        };
+
+        let start_pad =
+            |pad_config: conf::ParsedPadConfig,
+             bank: Option<usize>,
+             midi_sender: Arc<dyn midi_model::MidiSender + Send + Sync>| {
+                let info = server::api::PadInfo {
+                    controller: controller.controller_name.clone(),
+                    bank,
+                    status: pad_config.actor_config.pad_mapping.pad_id.status.as_u8(),
+                    fst_data_byte: pad_config
+                        .actor_config
+                        .pad_mapping
+                        .pad_id
+                        .fst_data_byte
+                        .as_u8(),
+                    handler: pad_config.handler_type,
+                    every_seconds: pad_config.actor_config.schedule_every.as_secs(),
+                    configuration: pad_config.configuration.clone(),
+                };
+                let handler: Arc<tokio::sync::Mutex<dyn PadHandler>> =
+                    match pad_config.handler_config {
+                        conf::PadHandlerConfig::Own(config) => Arc::new(tokio::sync::Mutex::new(
+                            create_handler(config, &ctx.k8s_client),
+                        )),
+                        conf::PadHandlerConfig::Shared(name) => shared_handlers[&name].clone(),
+                        conf::PadHandlerConfig::Source(config) => {
+                            let every = pad_config.actor_config.schedule_every;
+                            let (handle, actor) = actor::PadActor::start_source(
+                                create_source(config, every, &ctx.k8s_client, &ctx.webhooks),
+                                midi_sender,
+                                ctx.runtime.clone(),
+                                pad_config.actor_config,
+                            );
+                            ctx.pads.register(info, actor.control());
+                            return (handle, actor);
+                        }
+                    };
+
+                let (handle, actor) = actor::PadActor::start(
+                    handler,
+                    midi_sender,
+                    ctx.runtime.clone(),
+                    pad_config.actor_config,
+                );
+                ctx.pads.register(info, actor.control());
+                (handle, actor)
+            };
+
+        for pad_config in controller.pad_configs {
+            let (handle, actor) = start_pad(pad_config, None, midi_sender.clone());
+            listener.register(scoped(actor.pad_id.clone()), Arc::new(actor));
+            handles.push(handle);
+        }
+
+        if !controller.banks.is_empty() {
+            let selectors = controller
+                .banks
+                .iter()
+                .map(|b| b.selector.clone())
+                .collect();
+            let switcher =
+                BankSwitcher::new(midi_sender.clone(), controller.color_mapping, selectors);
+
+            let mut banked_actors: HashMap<actor::PadId, HashMap<usize, Arc<actor::PadActor>>> =
+                HashMap::new();
+            for (bank_idx, bank) in controller.banks.into_iter().enumerate() {
+                listener.register(scoped(bank.selector), switcher.selector(bank_idx));
+                for mut pad_config in bank.pad_configs {
+                    pad_config.actor_config.observer = Some(switcher.observer(bank_idx));
+                    let (handle, actor) =
+                        start_pad(pad_config, Some(bank_idx), switcher.view(bank_idx));
+                    banked_actors
+                        .entry(actor.pad_id.clone())
+                        .or_default()
+                        .insert(bank_idx, Arc::new(actor));
+                    handles.push(handle);
+                }
+            }
+
+            for (pad_id, actors) in banked_actors {
+                listener.register(scoped(pad_id), switcher.pad(actors));
+            }
+        }
+    }
 
     Ok(())
 }
 
-fn load_and_parse(config_path: &str) -> anyhow::Result<conf::ParsedPadConfigs> {
+fn create_handler(
+    config: conf::ParsedHandlerConfig,
+    k8s_client: &Arc<kubernetes::kubers::KubeRsBased>,
+) -> Box<dyn PadHandler> {
+    match config {
+        conf::ParsedHandlerConfig::K8S(config) => Box::new(k8s_handler::K8SDeploymentHandler::new(
+            k8s_client.clone(),
+            config,
+        )),
+        conf::ParsedHandlerConfig::K8SNamespace(config) => Box::new(
+            k8s_handler::K8SNamespaceHandler::new(k8s_client.clone(), config),
+        ),
+        conf::ParsedHandlerConfig::K8SEvents(config) => Box::new(
+            k8s_handler::K8SEventsHandler::new(k8s_client.clone(), config),
+        ),
+        conf::ParsedHandlerConfig::K8SCluster(config) => Box::new(
+            k8s_handler::K8SClusterHandler::new(k8s_client.clone(), config),
+        ),
+        conf::ParsedHandlerConfig::Script(config) => {
+            Box::new(script_handler::ScriptHandler::new(config))
+        }
+        conf::ParsedHandlerConfig::Prometheus(config) => {
+            Box::new(prometheus_handler::PrometheusHandler::new(*config))
+        }
+        conf::ParsedHandlerConfig::Alertmanager(config) => {
+            Box::new(alertmanager_handler::AlertmanagerHandler::new(*config))
+        }
+        conf::ParsedHandlerConfig::Tcp(config) => Box::new(tcp_handler::TcpHandler::new(*config)),
+        conf::ParsedHandlerConfig::Certificate(config) => {
+            Box::new(certificate_handler::CertificateHandler::new(*config))
+        }
+        conf::ParsedHandlerConfig::File(config) => {
+            Box::new(file_handler::FileHandler::new(*config))
+        }
+        conf::ParsedHandlerConfig::System(config) => {
+            Box::new(system_handler::SystemHandler::new(config))
+        }
+        conf::ParsedHandlerConfig::Composite { policy, children } => {
+            let children = children
+                .into_iter()
+                .map(|child| create_handler(child, k8s_client))
+                .collect();
+            Box::new(CompositeHandler::new(policy, children))
+        }
+    }
+}
+
+/// Sources checking the state periodically, in addition to watching it, are checked `every`.
+fn create_source(
+    config: conf::ParsedSourceConfig,
+    every: Duration,
+    k8s_client: &Arc<kubernetes::kubers::KubeRsBased>,
+    webhooks: &webhook::Webhooks,
+) -> Box<dyn actor::PadEventSource> {
+    match config {
+        conf::ParsedSourceConfig::Webhook(config) => {
+            Box::new(webhook::WebhookSource::new(config, webhooks))
+        }
+        conf::ParsedSourceConfig::K8S(config) => Box::new(k8s_handler::K8SDeploymentWatch::new(
+            k8s_client.clone(),
+            config,
+        )),
+        conf::ParsedSourceConfig::File(config) => {
+            Box::new(file_handler::FileWatch::new(*config, every))
+        }
+        conf::ParsedSourceConfig::LogTail(config) => {
+            Box::new(log_tail_handler::LogTailSource::new(*config))
+        }
+    }
+}
+
+/// Resolves controller for diagnostic commands.
+/// Ports of the configured controller are used, unless explicit controller is not configured.
+fn cli_target(explicit: &Option<String>, config_path: &str) -> anyhow::Result<cli::Target> {
+    let configuration = match (explicit, configuration::load_from_yaml(config_path)) {
+        (_, Ok(configuration)) => configuration,
+        (Some(controller), Err(_)) => return Ok(cli::Target::named(controller)),
+        (None, Err(e)) => return Err(e),
+    };
+
+    let controller = match explicit {
+        Some(controller) => match configuration.controller(controller) {
+            Some(configured) => configured,
+            None => return Ok(cli::Target::named(controller)),
+        },
+        None => match configuration.controllers.as_slice() {
+            [configured] => configured,
+            _ => anyhow::bail!("Select one of the configured controllers with --controller."),
+        },
+    };
+
+    Ok(cli::Target {
+        controller: controller.controller_name.clone(),
+        input_port: controller.ports.input(&controller.controller_name),
+        output_port: controller.ports.output(&controller.controller_name),
+    })
+}
+
+/// Logs to stderr, so that output of diagnostic commands is kept intact.
+fn init_logging(verbosity: u8, format: LogFormat) {
+    let level = match verbosity {
+        0 => "info",
+        1 => "debug",
+        _ => "trace",
+    };
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(format!("warn,rust_midi={level}")));
+    use tracing_subscriber::filter::LevelFilter;
+    use tracing_subscriber::prelude::*;
+
+    let fmt = tracing_subscriber::fmt::layer().with_writer(std::io::stderr);
+    let fmt = match format {
+        LogFormat::Text => fmt.with_filter(filter).boxed(),
+        LogFormat::Json => fmt.json().with_filter(filter).boxed(),
+    };
+    tracing_subscriber::registry()
+        .with(actor::WarningCapture.with_filter(LevelFilter::WARN))
+        .with(fmt)
+        .init();
+}
+
+fn load_and_parse(config_path: &str) -> anyhow::Result<conf::ParsedConfig> {
     let configuration = configuration::load_from_yaml(config_path)?;
     //set-up workers
-    Ok(configuration::parse(configuration))
+    configuration::parse(configuration)
 }
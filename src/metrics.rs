@@ -0,0 +1,171 @@
+//! Metrics of the monitor itself, exported by the server at `/metrics`.
+
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry,
+};
+
+use crate::worker::actor::{PadId, PadOutput};
+
+const PAD_LABELS: [&str; 3] = ["controller", "bank", "pad"];
+
+/// Values of the `outcome` label of handler invocations.
+const OUTCOMES: [&str; 4] = ["Ok", "NotOk", "TempError", "Custom"];
+
+struct Metrics {
+    registry: Registry,
+    invocations: IntCounterVec,
+    duration: HistogramVec,
+    presses: IntCounterVec,
+    paused: IntGaugeVec,
+    midi_sent: IntCounterVec,
+    midi_send_failures: IntCounterVec,
+}
+
+static METRICS: Lazy<Metrics> = Lazy::new(|| {
+    let invocations = IntCounterVec::new(
+        Opts::new(
+            "rust_midi_handler_invocations_total",
+            "Handler invocations by their outcome.",
+        ),
+        &["controller", "bank", "pad", "outcome"],
+    )
+    .unwrap();
+    let duration = HistogramVec::new(
+        HistogramOpts::new(
+            "rust_midi_handler_duration_seconds",
+            "Duration of handler invocations.",
+        ),
+        &PAD_LABELS,
+    )
+    .unwrap();
+    let presses = IntCounterVec::new(
+        Opts::new("rust_midi_pad_presses_total", "Pad presses received."),
+        &PAD_LABELS,
+    )
+    .unwrap();
+    let paused = IntGaugeVec::new(
+        Opts::new("rust_midi_pad_paused", "Whether the pad is paused."),
+        &PAD_LABELS,
+    )
+    .unwrap();
+    let midi_sent = IntCounterVec::new(
+        Opts::new(
+            "rust_midi_messages_sent_total",
+            "MIDI messages sent to the controller.",
+        ),
+        &["controller"],
+    )
+    .unwrap();
+    let midi_send_failures = IntCounterVec::new(
+        Opts::new(
+            "rust_midi_message_send_failures_total",
+            "MIDI messages which couldn't be sent to the controller.",
+        ),
+        &["controller"],
+    )
+    .unwrap();
+
+    let registry = Registry::new();
+    registry.register(Box::new(invocations.clone())).unwrap();
+    registry.register(Box::new(duration.clone())).unwrap();
+    registry.register(Box::new(presses.clone())).unwrap();
+    registry.register(Box::new(paused.clone())).unwrap();
+    registry.register(Box::new(midi_sent.clone())).unwrap();
+    registry
+        .register(Box::new(midi_send_failures.clone()))
+        .unwrap();
+
+    Metrics {
+        registry,
+        invocations,
+        duration,
+        presses,
+        paused,
+        midi_sent,
+        midi_send_failures,
+    }
+});
+
+/// All metrics in the Prometheus text format.
+pub fn render() -> String {
+    let encoder = prometheus::TextEncoder::new();
+    encoder
+        .encode_to_string(&METRICS.registry.gather())
+        .unwrap_or_default()
+}
+
+/// Metrics of the single pad.
+#[derive(Clone)]
+pub struct PadMetrics {
+    labels: [String; 3],
+}
+
+impl PadMetrics {
+    /// Bank is empty for pads outside banks.
+    pub fn new(controller: &str, bank: Option<usize>, pad_id: &PadId) -> PadMetrics {
+        PadMetrics {
+            labels: [
+                controller.to_string(),
+                bank.map(|bank| bank.to_string()).unwrap_or_default(),
+                pad_id.to_string(),
+            ],
+        }
+    }
+
+    fn labels(&self) -> [&str; 3] {
+        [&self.labels[0], &self.labels[1], &self.labels[2]]
+    }
+
+    pub fn invoked(&self, output: &PadOutput, duration: Duration) {
+        let outcome = match output {
+            PadOutput::Ok => OUTCOMES[0],
+            PadOutput::NotOk => OUTCOMES[1],
+            PadOutput::TempError => OUTCOMES[2],
+            PadOutput::Custom(_) => OUTCOMES[3],
+        };
+        let [controller, bank, pad] = self.labels();
+        METRICS
+            .invocations
+            .with_label_values(&[controller, bank, pad, outcome])
+            .inc();
+        METRICS
+            .duration
+            .with_label_values(&self.labels())
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn pressed(&self) {
+        METRICS.presses.with_label_values(&self.labels()).inc();
+    }
+
+    pub fn paused(&self, paused: bool) {
+        METRICS
+            .paused
+            .with_label_values(&self.labels())
+            .set(paused.into());
+    }
+
+    /// Removes all series of the pad, e.g. when it is stopped before the configuration is reloaded.
+    pub fn remove(&self) {
+        let [controller, bank, pad] = self.labels();
+        for outcome in OUTCOMES {
+            let _ = METRICS
+                .invocations
+                .remove_label_values(&[controller, bank, pad, outcome]);
+        }
+        let _ = METRICS.duration.remove_label_values(&self.labels());
+        let _ = METRICS.presses.remove_label_values(&self.labels());
+        let _ = METRICS.paused.remove_label_values(&self.labels());
+    }
+}
+
+/// Counters of messages sent to the controller.
+pub fn midi_sent(controller: &str) -> (IntCounter, IntCounter) {
+    (
+        METRICS.midi_sent.with_label_values(&[controller]),
+        METRICS.midi_send_failures.with_label_values(&[controller]),
+    )
+}
@@ -5,7 +5,8 @@ use std::thread::JoinHandle;
 
 use crossbeam_channel as cch;
 
-use crate::midi::model::{MidiMessage, MidiSendFailed, MidiSender};
+use crate::metrics;
+use crate::midi::model::{MidiMessage, MidiSendFailed, MidiSender, PortSelector};
 use crate::midi_model::{DataByte, MidiReceiver, Status};
 
 struct AsU8s(MidiMessage);
@@ -22,55 +23,58 @@ impl From<AsU8s> for [u8; 3] {
 
 pub struct MidirBasedSender {
     sender: cch::Sender<MidiMessage>,
+    send_failures: prometheus::IntCounter,
     _sending_loop: JoinHandle<()>,
 }
 
 impl MidirBasedSender {
-    pub fn new(controller: &str) -> anyhow::Result<MidirBasedSender> {
-        let mut midi_out = Self::prepare_midi_out_connection(controller)?;
+    pub fn new(controller: &str, port: &PortSelector) -> anyhow::Result<MidirBasedSender> {
+        let mut midi_out = Self::prepare_midi_out_connection(controller, port)?;
         let (sender, receiver) = cch::unbounded();
+        let (sent, send_failures) = metrics::midi_sent(controller);
+        let loop_send_failures = send_failures.clone();
         let _sending_loop = thread::spawn(move || {
             while let Ok(midi_msg) = receiver.recv() {
                 let bytes: [u8; 3] = (AsU8s(midi_msg)).into();
                 // Fire and forget.
-                let _ = midi_out.send(bytes.as_slice());
+                match midi_out.send(bytes.as_slice()) {
+                    Ok(()) => sent.inc(),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "MIDI message not sent.");
+                        loop_send_failures.inc()
+                    }
+                }
             }
         });
 
         Ok(MidirBasedSender {
             sender,
+            send_failures,
             _sending_loop,
         })
     }
 
     fn prepare_midi_out_connection(
         controller: &str,
+        port: &PortSelector,
     ) -> anyhow::Result<midir::MidiOutputConnection> {
-        let midi_port = midir::MidiOutput::new(&format!("{controller}-client"))?;
-        Self::set_up_connection(midi_port, controller).ok_or_else(|| {
-            anyhow::Error::msg(format!("Couldn't set up connection with {controller}."))
-        })
-    }
-
-    fn set_up_connection(
-        mo: midir::MidiOutput,
-        controller: &str,
-    ) -> Option<midir::MidiOutputConnection> {
-        mo.ports()
-            .iter()
-            .find_map(|p| {
-                mo.port_name(p)
-                    .ok()
-                    .filter(|pn| pn.contains(controller))
-                    .map(|_| p)
-            })
-            .map(|p| mo.connect(p, controller).unwrap())
+        let midi_output = midir::MidiOutput::new(&format!("{controller}-client"))?;
+        let ports = midi_output.ports();
+        let port_idx = port
+            .select(&port_names(&midi_output, &ports))
+            .map_err(|e| anyhow!("Couldn't set up output connection with {controller}. {e}"))?;
+        midi_output
+            .connect(&ports[port_idx], controller)
+            .map_err(|e| anyhow!("Failed connecting to MIDI Output Device. Reason: {e}"))
     }
 }
 
 impl MidiSender for MidirBasedSender {
     fn send(&self, msg: MidiMessage) -> Result<(), MidiSendFailed> {
-        self.sender.send(msg).map_err(|e| MidiSendFailed(e.into()))
+        self.sender.send(msg).map_err(|e| {
+            self.send_failures.inc();
+            MidiSendFailed(e.into())
+        })
     }
 }
 
@@ -81,63 +85,29 @@ pub struct MidirBasedReceiver {
 }
 
 impl MidirBasedReceiver {
-    pub fn new(controller: &str) -> anyhow::Result<MidirBasedReceiver> {
-        let midi_input = midir::MidiInput::new(&format!("{controller}-client"))?;
-        let input_port = Self::prepare_midi_in_port(controller, &midi_input)?;
-
+    pub fn new(controller: &str, port: &PortSelector) -> anyhow::Result<MidirBasedReceiver> {
         let (sender, receiver) = cch::unbounded();
 
-        let _connection = Mutex::new(
-            midi_input
-                .connect(
-                    &input_port,
-                    controller,
-                    move |_: u64, message: &[u8], _: &mut ()| {
-                        let status = message[0];
-                        let fst_db = message[1];
-                        let snd_db = message[2];
-
-                        let midi_msg = unsafe {
-                            MidiMessage {
-                                status: Status::from_u8_unsafe(status),
-                                fst_data_byte: DataByte::from_u8_unsafe(fst_db),
-                                snd_data_byte: DataByte::from_u8_unsafe(snd_db),
-                            }
-                        };
-
-                        let _ = sender.send(midi_msg);
-                    },
-                    (),
-                )
-                .map_err(|e| anyhow!("Failed connecting to MIDI Input Device. Reason: {e}"))?,
-        );
+        let _connection = Mutex::new(connect_input(controller, port, move |_, message| {
+            // Only channel messages are meaningful for pads.
+            if let [status, fst_db, snd_db] = *message {
+                let midi_msg = unsafe {
+                    MidiMessage {
+                        status: Status::from_u8_unsafe(status),
+                        fst_data_byte: DataByte::from_u8_unsafe(fst_db),
+                        snd_data_byte: DataByte::from_u8_unsafe(snd_db),
+                    }
+                };
+
+                let _ = sender.send(midi_msg);
+            }
+        })?);
 
         Ok(MidirBasedReceiver {
             receiver,
             _connection,
         })
     }
-
-    fn prepare_midi_in_port(
-        controller: &str,
-        midi_input: &midir::MidiInput,
-    ) -> anyhow::Result<midir::MidiInputPort> {
-        Self::set_up_port(midi_input, controller).ok_or_else(|| {
-            anyhow::Error::msg(format!("Couldn't set up connection with {controller}."))
-        })
-    }
-
-    fn set_up_port(mo: &midir::MidiInput, controller: &str) -> Option<midir::MidiInputPort> {
-        mo.ports()
-            .iter()
-            .find_map(|p| {
-                mo.port_name(p)
-                    .ok()
-                    .filter(|pn| pn.contains(controller))
-                    .map(|_| p)
-            })
-            .cloned()
-    }
 }
 
 impl MidiReceiver for MidirBasedReceiver {
@@ -145,3 +115,46 @@ impl MidiReceiver for MidirBasedReceiver {
         self.receiver.recv().ok()
     }
 }
+
+/// Connects to the selected input port of the controller and passes every raw message,
+/// together with its timestamp in microseconds, to the callback.
+pub fn connect_input<F>(
+    controller: &str,
+    port: &PortSelector,
+    mut callback: F,
+) -> anyhow::Result<midir::MidiInputConnection<()>>
+where
+    F: FnMut(u64, &[u8]) + Send + 'static,
+{
+    let midi_input = midir::MidiInput::new(&format!("{controller}-client"))?;
+    let ports = midi_input.ports();
+    let port_idx = port
+        .select(&port_names(&midi_input, &ports))
+        .map_err(|e| anyhow!("Couldn't set up input connection with {controller}. {e}"))?;
+
+    midi_input
+        .connect(
+            &ports[port_idx],
+            controller,
+            move |timestamp: u64, message: &[u8], _: &mut ()| callback(timestamp, message),
+            (),
+        )
+        .map_err(|e| anyhow!("Failed connecting to MIDI Input Device. Reason: {e}"))
+}
+
+pub fn input_port_names() -> anyhow::Result<Vec<String>> {
+    let midi_input = midir::MidiInput::new("rust_midi-ports")?;
+    Ok(port_names(&midi_input, &midi_input.ports()))
+}
+
+pub fn output_port_names() -> anyhow::Result<Vec<String>> {
+    let midi_output = midir::MidiOutput::new("rust_midi-ports")?;
+    Ok(port_names(&midi_output, &midi_output.ports()))
+}
+
+fn port_names<IO: midir::MidiIO>(midi_io: &IO, ports: &[IO::Port]) -> Vec<String> {
+    ports
+        .iter()
+        .map(|p| midi_io.port_name(p).unwrap_or_default())
+        .collect()
+}
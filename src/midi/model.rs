@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::extension::OptionExt;
 
 use thiserror;
@@ -62,6 +64,9 @@ impl TryFrom<u8> for DataByte {
     }
 }
 
+/// Second data byte sent by the controller once pad is pressed.
+pub const PRESSED: DataByte = DataByte(127);
+
 #[derive(Debug)]
 pub struct MidiMessage {
     pub status: Status,
@@ -69,6 +74,12 @@ pub struct MidiMessage {
     pub snd_data_byte: DataByte,
 }
 
+impl MidiMessage {
+    pub fn is_pressed(&self) -> bool {
+        self.snd_data_byte == PRESSED
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error(transparent)]
 pub struct MidiSendFailed(#[from] pub anyhow::Error);
@@ -77,9 +88,178 @@ pub trait MidiSender {
     fn send(&self, msg: MidiMessage) -> Result<(), MidiSendFailed>;
 
     fn send_and_forget(&self, msg: MidiMessage) {
-        let _ = self.send(msg);
+        if let Err(e) = self.send(msg) {
+            tracing::warn!(error = %e, "MIDI message not sent.");
+        }
     }
 }
 pub trait MidiReceiver {
     fn poll(&self) -> Option<MidiMessage>;
 }
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct PortPattern(regex::Regex);
+
+impl TryFrom<String> for PortPattern {
+    type Error = regex::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        regex::Regex::new(&value).map(PortPattern)
+    }
+}
+
+/// Rule used to pick MIDI port of the controller.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(try_from = "PortSelectorConfig")]
+pub enum PortSelector {
+    /// Port name is equal to the given one.
+    Name(String),
+    /// Port name contains the given text.
+    Contains(String),
+    /// Port name matches the given regular expression.
+    Regex(PortPattern),
+    /// Position of the port on the list of available ports.
+    Index(usize),
+}
+
+/// YAML representation of [`PortSelector`]: a map with exactly one of the keys.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PortSelectorConfig {
+    name: Option<String>,
+    contains: Option<String>,
+    regex: Option<PortPattern>,
+    index: Option<usize>,
+}
+
+impl TryFrom<PortSelectorConfig> for PortSelector {
+    type Error = String;
+
+    fn try_from(value: PortSelectorConfig) -> Result<Self, Self::Error> {
+        match value {
+            PortSelectorConfig {
+                name: Some(name),
+                contains: None,
+                regex: None,
+                index: None,
+            } => Ok(PortSelector::Name(name)),
+            PortSelectorConfig {
+                name: None,
+                contains: Some(text),
+                regex: None,
+                index: None,
+            } => Ok(PortSelector::Contains(text)),
+            PortSelectorConfig {
+                name: None,
+                contains: None,
+                regex: Some(regex),
+                index: None,
+            } => Ok(PortSelector::Regex(regex)),
+            PortSelectorConfig {
+                name: None,
+                contains: None,
+                regex: None,
+                index: Some(idx),
+            } => Ok(PortSelector::Index(idx)),
+            _ => Err(String::from(
+                "Expected port to be selected by exactly one of: name, contains, regex, index.",
+            )),
+        }
+    }
+}
+
+impl PortSelector {
+    pub fn matches(&self, port_idx: usize, port_name: &str) -> bool {
+        match self {
+            PortSelector::Name(name) => port_name == name,
+            PortSelector::Contains(text) => port_name.contains(text.as_str()),
+            PortSelector::Regex(PortPattern(regex)) => regex.is_match(port_name),
+            PortSelector::Index(idx) => port_idx == *idx,
+        }
+    }
+
+    /// Returns index of the single port matching the selector.
+    pub fn select(&self, port_names: &[String]) -> anyhow::Result<usize> {
+        let candidates: Vec<usize> = (0..port_names.len())
+            .filter(|&idx| self.matches(idx, &port_names[idx]))
+            .collect();
+
+        match candidates.as_slice() {
+            [idx] => Ok(*idx),
+            [] => Err(anyhow::anyhow!(
+                "No MIDI port matches {self}. Available ports: {port_names:?}."
+            )),
+            _ => {
+                let candidate_names: Vec<&String> =
+                    candidates.iter().map(|&idx| &port_names[idx]).collect();
+                Err(anyhow::anyhow!(
+                    "More than one MIDI port matches {self}. Candidates: {candidate_names:?}. Select the port by exact name, regex or index."
+                ))
+            }
+        }
+    }
+}
+
+impl fmt::Display for PortSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortSelector::Name(name) => write!(f, "name '{name}'"),
+            PortSelector::Contains(text) => write!(f, "text '{text}'"),
+            PortSelector::Regex(PortPattern(regex)) => write!(f, "regex '{regex}'"),
+            PortSelector::Index(idx) => write!(f, "index {idx}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ports() -> Vec<String> {
+        ["DDJ-XP2 MIDI 1", "DDJ-XP2 MIDI 2", "Launchpad X"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn parse(yaml: &str) -> Result<PortSelector, serde_yaml::Error> {
+        serde_yaml::from_str(yaml)
+    }
+
+    #[test]
+    fn parses_selector_with_exactly_one_key() {
+        assert!(
+            matches!(parse("name: DDJ-XP2 MIDI 1"), Ok(PortSelector::Name(name)) if name == "DDJ-XP2 MIDI 1")
+        );
+        assert!(
+            matches!(parse("contains: XP2"), Ok(PortSelector::Contains(text)) if text == "XP2")
+        );
+        assert!(matches!(
+            parse("regex: 'XP2 .* 2$'"),
+            Ok(PortSelector::Regex(_))
+        ));
+        assert!(matches!(parse("index: 2"), Ok(PortSelector::Index(2))));
+
+        assert!(parse("{name: a, index: 1}").is_err());
+        assert!(parse("{}").is_err());
+        assert!(parse("regex: '('").is_err());
+        assert!(parse("port: a").is_err());
+    }
+
+    #[test]
+    fn selects_single_matching_port() {
+        let ports = ports();
+
+        assert_eq!(
+            parse("name: Launchpad X").unwrap().select(&ports).unwrap(),
+            2
+        );
+        assert_eq!(
+            parse("regex: 'XP2 .* 2$'").unwrap().select(&ports).unwrap(),
+            1
+        );
+        assert_eq!(parse("index: 0").unwrap().select(&ports).unwrap(), 0);
+        assert!(parse("contains: XP2").unwrap().select(&ports).is_err());
+        assert!(parse("contains: APC").unwrap().select(&ports).is_err());
+    }
+}
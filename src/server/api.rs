@@ -0,0 +1,131 @@
+//! JSON API exposing state of all pads, which lets them be paused, resumed and triggered remotely.
+
+use std::sync::{Arc, RwLock};
+
+use hyper::{Body, Response, StatusCode};
+
+use crate::worker::actor::{PadControl, PadState};
+
+/// Static description of the pad, taken from the configuration.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PadInfo {
+    pub controller: String,
+    /// Index of the bank in the configuration, missing for pads outside banks.
+    pub bank: Option<usize>,
+    pub status: u8,
+    pub fst_data_byte: u8,
+    /// Type of the handler, e.g. `Prometheus`.
+    pub handler: &'static str,
+    pub every_seconds: u64,
+    /// Mapping of the pad, with secrets hidden.
+    pub configuration: serde_json::Value,
+}
+
+#[derive(serde::Serialize)]
+struct PadView {
+    id: usize,
+    #[serde(flatten)]
+    info: PadInfo,
+    #[serde(flatten)]
+    state: PadState,
+}
+
+struct Entry {
+    info: PadInfo,
+    control: PadControl,
+}
+
+/// Running pads, identified by the order of registration.
+#[derive(Clone, Default)]
+pub struct Pads(Arc<RwLock<Vec<Entry>>>);
+
+impl Pads {
+    pub fn register(&self, info: PadInfo, control: PadControl) {
+        self.0.write().unwrap().push(Entry { info, control });
+    }
+
+    /// Unregisters all pads, e.g. before the configuration is reloaded.
+    pub fn clear(&self) {
+        self.0.write().unwrap().clear();
+    }
+
+    fn view(id: usize, entry: &Entry) -> PadView {
+        PadView {
+            id,
+            info: entry.info.clone(),
+            state: entry.control.state(),
+        }
+    }
+
+    fn control(&self, id: usize) -> Option<PadControl> {
+        self.0
+            .read()
+            .unwrap()
+            .get(id)
+            .map(|entry| entry.control.clone())
+    }
+}
+
+/// Requests reload of the configuration. Receives the error, when the new configuration is rejected.
+pub type ReloadRequest = tokio::sync::oneshot::Sender<Result<(), String>>;
+
+pub(super) fn list(pads: &Pads) -> Response<Body> {
+    let views: Vec<PadView> = pads
+        .0
+        .read()
+        .unwrap()
+        .iter()
+        .enumerate()
+        .map(|(id, entry)| Pads::view(id, entry))
+        .collect();
+    json(&views)
+}
+
+pub(super) fn get(pads: &Pads, id: &str) -> Response<Body> {
+    let entries = pads.0.read().unwrap();
+    match id
+        .parse()
+        .ok()
+        .and_then(|id: usize| Some((id, entries.get(id)?)))
+    {
+        Some((id, entry)) => json(&Pads::view(id, entry)),
+        None => super::response(StatusCode::NOT_FOUND, &format!("Pad '{id}' not found.")),
+    }
+}
+
+pub(super) fn act(pads: &Pads, id: &str, action: &str) -> Response<Body> {
+    let Some(control) = id.parse().ok().and_then(|id| pads.control(id)) else {
+        return super::response(StatusCode::NOT_FOUND, &format!("Pad '{id}' not found."));
+    };
+    match action {
+        "pause" => control.pause(),
+        "resume" => control.resume(),
+        "trigger" => control.trigger(),
+        _ => return super::response(StatusCode::NOT_FOUND, "Not found."),
+    }
+    // Commands are processed by the pad asynchronously.
+    super::response(StatusCode::ACCEPTED, "")
+}
+
+pub(super) async fn reload(reload: &async_channel::Sender<ReloadRequest>) -> Response<Body> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    if reload.send(sender).await.is_err() {
+        return super::response(StatusCode::SERVICE_UNAVAILABLE, "Reload is not available.");
+    }
+    match receiver.await {
+        Ok(Ok(())) => super::response(StatusCode::NO_CONTENT, ""),
+        Ok(Err(e)) => super::response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!("Configuration rejected: {e}"),
+        ),
+        Err(_) => super::response(StatusCode::SERVICE_UNAVAILABLE, "Reload is not available."),
+    }
+}
+
+fn json<T: serde::Serialize>(value: &T) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(value).unwrap()))
+        .unwrap()
+}
@@ -0,0 +1,333 @@
+//! Embedded HTTP server, which lets CI pipelines and scripts push states of webhook pads,
+//! exposes the control and status API under `/api`, and metrics of the monitor at `/metrics`.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::body::HttpBody;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use tokio::task::JoinHandle;
+
+use crate::metrics;
+use crate::worker::actor::OutputConfig;
+use crate::worker::webhook::Webhooks;
+
+pub mod api;
+
+/// Limit of request bodies, which are tiny JSON documents.
+const MAX_BODY_BYTES: usize = 4 * 1024;
+
+pub struct ServerState {
+    pub webhooks: Webhooks,
+    pub pads: api::Pads,
+    pub reload: async_channel::Sender<api::ReloadRequest>,
+}
+
+/// Body of `POST /pads/{name}`, e.g. `{"state": "not_ok"}` or `{"state": 114}`.
+#[derive(serde::Deserialize)]
+struct StateUpdate {
+    state: OutputConfig,
+}
+
+/// Binds the address and serves requests in the background. Returns the bound address.
+pub fn start(
+    listen: SocketAddr,
+    state: Arc<ServerState>,
+) -> anyhow::Result<(SocketAddr, JoinHandle<()>)> {
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(handle(&state, request).await) }
+            }))
+        }
+    });
+    let server = hyper::Server::try_bind(&listen)
+        .map_err(|e| anyhow::anyhow!("Server can't listen on {listen}: {e}"))?
+        .serve(make_service);
+    let address = server.local_addr();
+    let running = tokio::spawn(async move {
+        let _ = server.await;
+    });
+    Ok((address, running))
+}
+
+async fn handle(state: &ServerState, request: Request<Body>) -> Response<Body> {
+    let segments: Vec<&str> = request.uri().path().trim_matches('/').split('/').collect();
+
+    match (request.method(), segments.as_slice()) {
+        (&Method::POST, ["pads", name]) => {
+            let name = name.to_string();
+            update_pad(state, &name, request.into_body()).await
+        }
+        (_, ["pads", _]) => response(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed."),
+        (&Method::GET, ["metrics"]) => Response::builder()
+            .header(hyper::header::CONTENT_TYPE, prometheus::TEXT_FORMAT)
+            .body(Body::from(metrics::render()))
+            .unwrap(),
+        (&Method::GET, ["api", "pads"]) => api::list(&state.pads),
+        (&Method::GET, ["api", "pads", id]) => api::get(&state.pads, id),
+        (&Method::POST, ["api", "pads", id, action]) => api::act(&state.pads, id, action),
+        (&Method::POST, ["api", "reload"]) => api::reload(&state.reload).await,
+        (_, ["api", "pads"] | ["api", "pads", _] | ["api", "pads", _, _] | ["api", "reload"]) => {
+            response(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed.")
+        }
+        _ => response(StatusCode::NOT_FOUND, "Not found."),
+    }
+}
+
+async fn update_pad(state: &ServerState, name: &str, body: Body) -> Response<Body> {
+    let body = match read_body(body).await {
+        Ok(body) => body,
+        Err(response) => return response,
+    };
+    let update: StateUpdate = match serde_json::from_slice(&body) {
+        Ok(update) => update,
+        Err(e) => return response(StatusCode::BAD_REQUEST, &format!("Invalid state: {e}")),
+    };
+
+    if state.webhooks.publish(name, update.state.into()) {
+        response(StatusCode::NO_CONTENT, "")
+    } else {
+        response(StatusCode::NOT_FOUND, &format!("Pad '{name}' not found."))
+    }
+}
+
+/// Reads the body, failing with the response once it exceeds `MAX_BODY_BYTES`.
+async fn read_body(mut body: Body) -> Result<Vec<u8>, Response<Body>> {
+    let mut read = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk
+            .map_err(|e| response(StatusCode::BAD_REQUEST, &format!("Body can't be read: {e}")))?;
+        if read.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                &format!("Body exceeds {MAX_BODY_BYTES} bytes."),
+            ));
+        }
+        read.extend_from_slice(&chunk);
+    }
+    Ok(read)
+}
+
+fn response(status: StatusCode, message: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(message.to_string()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::metrics::PadMetrics;
+    use crate::midi::model::{DataByte, MidiMessage, MidiSendFailed, MidiSender, Status};
+    use crate::worker::actor::{
+        ColorMapping, Config, PadActor, PadHandler, PadId, PadMapping, PadOutput, TokioRuntime,
+    };
+
+    use super::*;
+
+    struct Failing;
+
+    #[async_trait::async_trait]
+    impl PadHandler for Failing {
+        async fn handle(&mut self) -> PadOutput {
+            PadOutput::NotOk
+        }
+    }
+
+    struct Disconnected;
+
+    impl MidiSender for Disconnected {
+        fn send(&self, _msg: MidiMessage) -> Result<(), MidiSendFailed> {
+            Ok(())
+        }
+    }
+
+    fn byte(value: u8) -> DataByte {
+        DataByte::from_u8(value).unwrap()
+    }
+
+    async fn post(url: &str, body: &str) -> StatusCode {
+        reqwest::Client::new()
+            .post(url)
+            .body(body.to_string())
+            .send()
+            .await
+            .unwrap()
+            .status()
+    }
+
+    async fn pad(url: &str) -> serde_json::Value {
+        reqwest::get(url).await.unwrap().json().await.unwrap()
+    }
+
+    /// Waits until the pad processes the command.
+    async fn pad_until(url: &str, condition: impl Fn(&serde_json::Value) -> bool) {
+        for _ in 0..50 {
+            if condition(&pad(url).await) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("Pad has not reached the expected state: {}", pad(url).await);
+    }
+
+    #[test]
+    fn api_reports_and_controls_pads() {
+        let tokio_runtime = Arc::new(
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .unwrap(),
+        );
+        let runtime = Arc::new(TokioRuntime::new(tokio_runtime.clone()));
+        tokio_runtime.block_on(async move {
+            let pad_id = PadId {
+                status: Status::from_u8(0x90).unwrap(),
+                fst_data_byte: byte(0x24),
+            };
+            let metrics = PadMetrics::new("launchpad", None, &pad_id);
+            let (_, actor) = PadActor::start(
+                Arc::new(tokio::sync::Mutex::new(Failing)),
+                Arc::new(Disconnected),
+                runtime,
+                Config {
+                    pad_mapping: PadMapping {
+                        pad_id,
+                        color_mapping: Arc::new(ColorMapping {
+                            ok: byte(1),
+                            action_triggerred: byte(2),
+                            transient_error: byte(3),
+                            not_ok: byte(4),
+                            initial: byte(5),
+                            paused: byte(6),
+                        }),
+                    },
+                    schedule_every: Duration::from_secs(60),
+                    metrics,
+                    span: tracing::Span::none(),
+                    observer: None,
+                },
+            );
+            let pads = api::Pads::default();
+            pads.register(
+                api::PadInfo {
+                    controller: "launchpad".to_string(),
+                    bank: None,
+                    status: 0x90,
+                    fst_data_byte: 0x24,
+                    handler: "Script",
+                    every_seconds: 60,
+                    configuration: serde_json::json!({"type": "Script", "command": "false"}),
+                },
+                actor.control(),
+            );
+
+            let (reload, reload_requests) = async_channel::bounded(1);
+            let state = Arc::new(ServerState {
+                webhooks: Webhooks::default(),
+                pads,
+                reload,
+            });
+            let (address, server) = start(SocketAddr::from(([127, 0, 0, 1], 0)), state).unwrap();
+            let url = format!("http://{address}/api/pads/0");
+
+            pad_until(&url, |pad| pad["last_output"] == "not_ok").await;
+            let listed = pad(&format!("http://{address}/api/pads")).await;
+            assert_eq!(listed[0]["handler"], "Script");
+            assert_eq!(listed[0]["fst_data_byte"], 0x24);
+            assert!(listed[0]["last_run_millis"].is_u64());
+            assert_eq!(listed[0]["configuration"]["command"], "false");
+
+            assert_eq!(
+                post(&format!("{url}/pause"), "").await,
+                StatusCode::ACCEPTED
+            );
+            pad_until(&url, |pad| pad["paused"] == true).await;
+            let metrics = reqwest::get(format!("http://{address}/metrics"))
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap();
+            let pad_labels = r#"bank="",controller="launchpad",pad="0x90/0x24""#;
+            assert!(metrics.contains(&format!("rust_midi_pad_paused{{{pad_labels}}} 1")));
+            assert!(metrics.contains(
+                r#"rust_midi_handler_invocations_total{bank="",controller="launchpad",outcome="NotOk",pad="0x90/0x24"} 1"#
+            ));
+            assert_eq!(
+                post(&format!("{url}/resume"), "").await,
+                StatusCode::ACCEPTED
+            );
+            pad_until(&url, |pad| pad["paused"] == false).await;
+            assert_eq!(
+                post(&format!("http://{address}/api/pads/1/trigger"), "").await,
+                StatusCode::NOT_FOUND
+            );
+
+            tokio::spawn(async move {
+                let reply: api::ReloadRequest = reload_requests.recv().await.unwrap();
+                let _ = reply.send(Err("Invalid configuration.".to_string()));
+            });
+            assert_eq!(
+                post(&format!("http://{address}/api/reload"), "").await,
+                StatusCode::UNPROCESSABLE_ENTITY
+            );
+
+            server.abort();
+        });
+    }
+
+    #[tokio::test]
+    async fn posted_state_reaches_webhook_pads() {
+        let webhooks = Webhooks::default();
+        let updates = webhooks.register("deploy");
+        let (reload, _) = async_channel::bounded(1);
+        let state = Arc::new(ServerState {
+            webhooks,
+            pads: Default::default(),
+            reload,
+        });
+        let (address, server) = start(SocketAddr::from(([127, 0, 0, 1], 0)), state).unwrap();
+        let url = format!("http://{address}/pads/deploy");
+
+        assert_eq!(
+            post(&url, r#"{"state": "not_ok"}"#).await,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            post(&url, r#"{"state": 114}"#).await,
+            StatusCode::NO_CONTENT
+        );
+        let received = |updates: &async_channel::Receiver<PadOutput>| updates.try_recv().unwrap();
+        assert_eq!(received(&updates), PadOutput::NotOk);
+        assert_eq!(
+            received(&updates),
+            PadOutput::Custom(DataByte::from_u8(114).unwrap())
+        );
+
+        assert_eq!(
+            post(&url, r#"{"state": "green"}"#).await,
+            StatusCode::BAD_REQUEST
+        );
+        let padded = format!(r#"{{"state": "ok"{}}}"#, " ".repeat(MAX_BODY_BYTES));
+        assert_eq!(post(&url, &padded).await, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            post(
+                &format!("http://{address}/pads/unknown"),
+                r#"{"state": "ok"}"#
+            )
+            .await,
+            StatusCode::NOT_FOUND
+        );
+        assert!(updates.is_empty());
+
+        server.abort();
+    }
+}
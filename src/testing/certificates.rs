@@ -0,0 +1,80 @@
+//! Self-signed certificates and local TLS listener.
+
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use openssl::asn1::{Asn1Integer, Asn1Time};
+use openssl::bn::BigNum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use openssl::x509::{X509NameBuilder, X509};
+use tokio::net::TcpListener;
+
+const DAY_SECONDS: i64 = 24 * 60 * 60;
+
+pub struct TestCertificate {
+    pub cert_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+}
+
+/// Certificate of `localhost`, expiring in `valid_days`. Negative number gives already expired certificate.
+pub fn self_signed(valid_days: i64) -> TestCertificate {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    // Additional hour keeps the number of remaining days stable during the test.
+    let not_after = now + valid_days * DAY_SECONDS + 60 * 60;
+    let not_before = not_after.min(now) - DAY_SECONDS;
+
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "localhost").unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = Asn1Integer::from_bn(&BigNum::from_u32(1).unwrap()).unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::from_unix(not_before).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::from_unix(not_after).unwrap())
+        .unwrap();
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+
+    TestCertificate {
+        cert_pem: builder.build().to_pem().unwrap(),
+        key_pem: key.private_key_to_pem_pkcs8().unwrap(),
+    }
+}
+
+/// Starts TLS listener presenting the certificate. Accepted connections are kept open until the client closes them.
+pub async fn serve_tls(certificate: &TestCertificate) -> SocketAddr {
+    let identity =
+        native_tls::Identity::from_pkcs8(&certificate.cert_pem, &certificate.key_pem).unwrap();
+    let acceptor: tokio_native_tls::TlsAcceptor =
+        native_tls::TlsAcceptor::new(identity).unwrap().into();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                if let Ok(mut stream) = acceptor.accept(stream).await {
+                    let _ = tokio::io::copy(&mut stream, &mut tokio::io::sink()).await;
+                }
+            });
+        }
+    });
+    address
+}
@@ -0,0 +1,59 @@
+//! Fixtures shared by tests of handlers and sources.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::midi::model::DataByte;
+use crate::worker::actor::{Command, PadOutput};
+
+/// Custom output standing for a warning color.
+pub fn warning() -> PadOutput {
+    PadOutput::Custom(DataByte::from_u8(0x72).unwrap())
+}
+
+/// Empty directory, which is removed with its content when dropped.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Directory is unique to the caller, even when tests run in parallel.
+    pub fn new(name: &str) -> TempDir {
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "rust-midi-{name}-{}-{}",
+            std::process::id(),
+            CREATED.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Waits for the next command sent to the pad, failing the test after 5 seconds.
+pub async fn next_command(commands: &async_channel::Receiver<Command>) -> Command {
+    tokio::time::timeout(Duration::from_secs(5), commands.recv())
+        .await
+        .expect("No command has been sent to the pad.")
+        .unwrap()
+}
+
+/// Waits for the next output emitted by the event source.
+pub async fn emitted(commands: &async_channel::Receiver<Command>) -> PadOutput {
+    let Command::Output(output) = next_command(commands).await else {
+        panic!("Source emitted other command than output.");
+    };
+    output
+}
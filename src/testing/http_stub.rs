@@ -0,0 +1,133 @@
+//! Local HTTP server responding with canned responses and recording received requests.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, StatusCode};
+use tokio::task::JoinHandle;
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Default)]
+struct StubState {
+    /// Responses by request path. Unknown paths are answered with 404.
+    responses: HashMap<String, (StatusCode, String)>,
+    requests: Vec<RecordedRequest>,
+    delay: Duration,
+}
+
+pub struct HttpStub {
+    state: Arc<Mutex<StubState>>,
+    address: SocketAddr,
+    server: JoinHandle<()>,
+}
+
+impl HttpStub {
+    pub async fn start() -> HttpStub {
+        let state: Arc<Mutex<StubState>> = Default::default();
+
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let state = state.clone();
+                    async move { Ok::<_, Infallible>(handle(&state, request).await) }
+                }))
+            }
+        });
+        let server =
+            hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let address = server.local_addr();
+        let server = tokio::spawn(async move {
+            let _ = server.await;
+        });
+
+        HttpStub {
+            state,
+            address,
+            server,
+        }
+    }
+
+    /// Base URL of the stub, e.g. `http://127.0.0.1:41234`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub fn respond(&self, path: &str, status: StatusCode, body: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .insert(path.to_string(), (status, body.to_string()));
+    }
+
+    /// Delays all responses, e.g. to trigger client timeouts.
+    pub fn set_delay(&self, delay: Duration) {
+        self.state.lock().unwrap().delay = delay;
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+impl Drop for HttpStub {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn handle(state: &Mutex<StubState>, request: Request<Body>) -> Response<Body> {
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+    let recorded = RecordedRequest {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        query: parts
+            .uri
+            .query()
+            .map(|query| {
+                form_urlencoded::parse(query.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_default(),
+        headers: parts
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+        body: String::from_utf8_lossy(&body).to_string(),
+    };
+
+    let (delay, (status, body)) = {
+        let mut state = state.lock().unwrap();
+        let response = state
+            .responses
+            .get(&recorded.path)
+            .cloned()
+            .unwrap_or((StatusCode::NOT_FOUND, String::new()));
+        state.requests.push(recorded);
+        (state.delay, response)
+    };
+    tokio::time::sleep(delay).await;
+
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
@@ -0,0 +1,432 @@
+//! Local stub of the Kubernetes API server, serving objects kept in memory.
+//! It implements the subset of the API used by the tool: get, list with label and field selectors, watch and `/readyz`.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use k8s_openapi::{Metadata, Resource};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::kubernetes::kubers::KubeRsBased;
+use crate::kubernetes::model::ClusterContext;
+
+const CONTEXT: &str = "stub";
+
+type Query = HashMap<String, String>;
+
+struct Watcher {
+    query: Query,
+    events: mpsc::UnboundedSender<Value>,
+}
+
+#[derive(Default)]
+struct Collection {
+    objects: Vec<Value>,
+    watchers: Vec<Watcher>,
+}
+
+impl Collection {
+    fn notify(&mut self, event_type: &str, object: &Value) {
+        self.watchers.retain(|watcher| {
+            !matches(object, &watcher.query)
+                || watcher
+                    .events
+                    .send(json!({ "type": event_type, "object": object }))
+                    .is_ok()
+        });
+    }
+}
+
+struct StubState {
+    /// Objects by collection path, e.g. `/apis/apps/v1/namespaces/default/deployments`.
+    collections: HashMap<String, Collection>,
+    resource_version: u64,
+    readyz: StatusCode,
+}
+
+pub struct KubeStub {
+    state: Arc<Mutex<StubState>>,
+    kubeconfig: PathBuf,
+    server: JoinHandle<()>,
+}
+
+impl KubeStub {
+    /// Starts the stub on a random local port and writes kubeconfig pointing at it.
+    pub async fn start() -> KubeStub {
+        let state = Arc::new(Mutex::new(StubState {
+            collections: HashMap::new(),
+            resource_version: 0,
+            readyz: StatusCode::OK,
+        }));
+
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let response = handle(&state, request);
+                    async move { Ok::<_, Infallible>(response) }
+                }))
+            }
+        });
+        let server =
+            hyper::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let address = server.local_addr();
+        let server = tokio::spawn(async move {
+            let _ = server.await;
+        });
+
+        let kubeconfig = std::env::temp_dir().join(format!("kube-stub-{}.yaml", address.port()));
+        std::fs::write(&kubeconfig, render_kubeconfig(address)).unwrap();
+
+        KubeStub {
+            state,
+            kubeconfig,
+            server,
+        }
+    }
+
+    /// Client using the generated kubeconfig.
+    pub fn client(&self) -> Arc<KubeRsBased> {
+        Arc::new(KubeRsBased {
+            kubeconfig: Some(self.kubeconfig.clone()),
+        })
+    }
+
+    pub fn context(&self) -> ClusterContext {
+        ClusterContext(CONTEXT.to_string())
+    }
+
+    /// Adds the object or replaces the one with the same name. Watchers are notified.
+    pub fn put<K>(&self, object: &K)
+    where
+        K: Resource + Metadata<Ty = ObjectMeta> + serde::Serialize,
+    {
+        let metadata = object.metadata();
+        let name = metadata.name.clone().expect("Object must have a name.");
+        let path = collection_path::<K>(metadata.namespace.as_deref());
+
+        let mut state = self.state.lock().unwrap();
+        state.resource_version += 1;
+        let mut value = serde_json::to_value(object).unwrap();
+        value["metadata"]["resourceVersion"] = json!(state.resource_version.to_string());
+        if value["metadata"]["uid"].is_null() {
+            value["metadata"]["uid"] = json!(format!("{path}/{name}"));
+        }
+
+        let collection = state.collections.entry(path).or_default();
+        let existing = collection
+            .objects
+            .iter_mut()
+            .find(|existing| existing["metadata"]["name"] == json!(name));
+        let event_type = match existing {
+            Some(existing) => {
+                *existing = value.clone();
+                "MODIFIED"
+            }
+            None => {
+                collection.objects.push(value.clone());
+                "ADDED"
+            }
+        };
+        collection.notify(event_type, &value);
+    }
+
+    /// Removes the object. Watchers are notified.
+    pub fn delete<K: Resource>(&self, namespace: Option<&str>, name: &str) {
+        let path = collection_path::<K>(namespace);
+        let mut state = self.state.lock().unwrap();
+        let Some(collection) = state.collections.get_mut(&path) else {
+            return;
+        };
+
+        if let Some(idx) = collection
+            .objects
+            .iter()
+            .position(|object| object["metadata"]["name"] == json!(name))
+        {
+            let object = collection.objects.remove(idx);
+            collection.notify("DELETED", &object);
+        }
+    }
+
+    /// Sets the status of `/readyz` response, e.g. 500 when some of the checks fail.
+    pub fn set_readyz(&self, code: StatusCode) {
+        self.state.lock().unwrap().readyz = code;
+    }
+
+    /// Stops accepting connections, as if API server was unreachable.
+    pub fn stop(&self) {
+        self.server.abort();
+    }
+}
+
+impl Drop for KubeStub {
+    fn drop(&mut self) {
+        self.server.abort();
+        let _ = std::fs::remove_file(&self.kubeconfig);
+    }
+}
+
+fn render_kubeconfig(address: SocketAddr) -> String {
+    format!(
+        "apiVersion: v1
+kind: Config
+clusters:
+  - name: {CONTEXT}
+    cluster:
+      server: http://{address}
+users:
+  - name: {CONTEXT}
+    user:
+      token: {CONTEXT}
+contexts:
+  - name: {CONTEXT}
+    context:
+      cluster: {CONTEXT}
+      user: {CONTEXT}
+current-context: {CONTEXT}
+"
+    )
+}
+
+fn collection_path<K: Resource>(namespace: Option<&str>) -> String {
+    let api = if K::GROUP.is_empty() {
+        format!("/api/{}", K::VERSION)
+    } else {
+        format!("/apis/{}/{}", K::GROUP, K::VERSION)
+    };
+    match namespace {
+        Some(namespace) => format!("{api}/namespaces/{namespace}/{}", K::URL_PATH_SEGMENT),
+        None => format!("{api}/{}", K::URL_PATH_SEGMENT),
+    }
+}
+
+/// Splits request path into collection path and optional object name.
+fn parse_path(path: &str) -> Option<(String, Option<String>)> {
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let api_len = match segments.first() {
+        Some(&"api") => 2,
+        Some(&"apis") => 3,
+        _ => return None,
+    };
+    let resource = segments.get(api_len..)?;
+    let resource_len = match resource {
+        ["namespaces", _, _, ..] => 3,
+        _ => 1,
+    };
+    if resource.len() < resource_len || resource.len() > resource_len + 1 {
+        return None;
+    }
+
+    let collection = format!("/{}", segments[..api_len + resource_len].join("/"));
+    let name = resource.get(resource_len).map(|name| name.to_string());
+    Some((collection, name))
+}
+
+/// Checks `labelSelector` and `fieldSelector` with equality requirements, e.g. `app=redis,tier!=cache`.
+fn matches(object: &Value, query: &Query) -> bool {
+    let label = |key: &str| object["metadata"]["labels"][key].as_str().map(String::from);
+    let field = |path: &str| {
+        path.split('.')
+            .try_fold(object, |value, key| value.get(key))
+            .and_then(|value| value.as_str())
+            .map(String::from)
+    };
+
+    selector_matches(query.get("labelSelector"), label)
+        && selector_matches(query.get("fieldSelector"), field)
+}
+
+fn selector_matches(selector: Option<&String>, lookup: impl Fn(&str) -> Option<String>) -> bool {
+    let Some(selector) = selector else {
+        return true;
+    };
+    selector
+        .split(',')
+        .filter(|requirement| !requirement.is_empty())
+        .all(|requirement| match requirement.split_once("!=") {
+            Some((key, value)) => lookup(key).as_deref() != Some(value),
+            None => {
+                let (key, value) = requirement
+                    .split_once("==")
+                    .or_else(|| requirement.split_once('='))
+                    .unwrap_or((requirement, ""));
+                match value {
+                    "" => lookup(key).is_some(),
+                    value => lookup(key).as_deref() == Some(value),
+                }
+            }
+        })
+}
+
+fn handle(state: &Mutex<StubState>, request: Request<Body>) -> Response<Body> {
+    let query: Query = request
+        .uri()
+        .query()
+        .map(|query| {
+            form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut state = state.lock().unwrap();
+
+    if request.method() != Method::GET {
+        return status(StatusCode::METHOD_NOT_ALLOWED, "MethodNotAllowed");
+    }
+    if request.uri().path() == "/readyz" {
+        return match state.readyz {
+            StatusCode::OK => response(StatusCode::OK, Body::from("ok")),
+            StatusCode::FORBIDDEN => status(StatusCode::FORBIDDEN, "Forbidden"),
+            code => response(code, Body::from("readyz check failed")),
+        };
+    }
+    let Some((path, name)) = parse_path(request.uri().path()) else {
+        return status(StatusCode::NOT_FOUND, "NotFound");
+    };
+
+    let resource_version = state.resource_version.to_string();
+    let collection = state.collections.entry(path).or_default();
+    match name {
+        Some(name) => collection
+            .objects
+            .iter()
+            .find(|object| object["metadata"]["name"] == json!(name))
+            .map(|object| response(StatusCode::OK, Body::from(object.to_string())))
+            .unwrap_or_else(|| status(StatusCode::NOT_FOUND, "NotFound")),
+        None if matches!(query.get("watch").map(String::as_str), Some("true" | "1")) => {
+            watch(collection, query)
+        }
+        None => {
+            let items: Vec<&Value> = collection
+                .objects
+                .iter()
+                .filter(|object| matches(object, &query))
+                .collect();
+            let list = json!({
+                "apiVersion": "v1",
+                "kind": "List",
+                "metadata": { "resourceVersion": resource_version },
+                "items": items,
+            });
+            response(StatusCode::OK, Body::from(list.to_string()))
+        }
+    }
+}
+
+/// Streams changes of the collection as newline delimited watch events.
+/// Without `resourceVersion`, current objects are sent first as `ADDED`.
+fn watch(collection: &mut Collection, query: Query) -> Response<Body> {
+    let (events, receiver) = mpsc::unbounded_channel();
+    if matches!(
+        query.get("resourceVersion").map(String::as_str),
+        None | Some("" | "0")
+    ) {
+        for object in collection
+            .objects
+            .iter()
+            .filter(|object| matches(object, &query))
+        {
+            let _ = events.send(json!({ "type": "ADDED", "object": object }));
+        }
+    }
+    collection.watchers.push(Watcher { query, events });
+
+    let events = futures::stream::unfold(receiver, |mut receiver| async move {
+        let event = receiver.recv().await?;
+        Some((Ok::<_, Infallible>(format!("{event}\n")), receiver))
+    });
+    response(StatusCode::OK, Body::wrap_stream(events))
+}
+
+fn response(status: StatusCode, body: Body) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .unwrap()
+}
+
+fn status(code: StatusCode, reason: &str) -> Response<Body> {
+    let status = json!({
+        "apiVersion": "v1",
+        "kind": "Status",
+        "status": "Failure",
+        "message": reason,
+        "reason": reason,
+        "code": code.as_u16(),
+    });
+    response(code, Body::from(status.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use k8s_openapi::api::core::v1::Pod;
+    use kube_client::api::{ListParams, WatchEvent};
+    use kube_client::{Api, Client, Config};
+
+    use super::*;
+
+    fn pod(name: &str, app: &str) -> Pod {
+        serde_json::from_value(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": { "name": name, "namespace": "default", "labels": { "app": app } },
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn watch_streams_changes_of_matching_objects() {
+        let stub = KubeStub::start().await;
+        let kubeconfig = kube_client::config::Kubeconfig::read_from(&stub.kubeconfig).unwrap();
+        let config = Config::from_custom_kubeconfig(kubeconfig, &Default::default())
+            .await
+            .unwrap();
+        let pods: Api<Pod> = Api::namespaced(Client::try_from(config).unwrap(), "default");
+
+        stub.put(&pod("redis-1", "redis"));
+        let list = pods
+            .list(&ListParams::default().labels("app=redis"))
+            .await
+            .unwrap();
+        assert_eq!(list.items.len(), 1);
+
+        let resource_version = list.metadata.resource_version.unwrap();
+        let mut events = pods
+            .watch(
+                &ListParams::default().labels("app=redis"),
+                &resource_version,
+            )
+            .await
+            .unwrap()
+            .boxed();
+
+        stub.put(&pod("postgres-1", "postgres"));
+        stub.put(&pod("redis-2", "redis"));
+        stub.delete::<Pod>(Some("default"), "redis-1");
+
+        match events.next().await {
+            Some(Ok(WatchEvent::Added(pod))) => {
+                assert_eq!(pod.metadata.name.as_deref(), Some("redis-2"))
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+        match events.next().await {
+            Some(Ok(WatchEvent::Deleted(pod))) => {
+                assert_eq!(pod.metadata.name.as_deref(), Some("redis-1"))
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+}
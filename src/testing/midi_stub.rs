@@ -0,0 +1,45 @@
+//! MIDI sender recording messages instead of sending them to a controller.
+
+use std::sync::{Arc, Mutex};
+
+use crate::midi::model::{DataByte, MidiMessage, MidiSendFailed, MidiSender};
+use crate::worker::actor::ColorMapping;
+
+/// Sent message as `(status, fst_data_byte, snd_data_byte)`.
+pub type Sent = (u8, u8, u8);
+
+#[derive(Default)]
+pub struct RecordingSender {
+    sent: Mutex<Vec<Sent>>,
+}
+
+impl RecordingSender {
+    /// Returns messages sent since the last call.
+    pub fn take(&self) -> Vec<Sent> {
+        std::mem::take(&mut self.sent.lock().unwrap())
+    }
+}
+
+impl MidiSender for RecordingSender {
+    fn send(&self, msg: MidiMessage) -> Result<(), MidiSendFailed> {
+        self.sent.lock().unwrap().push((
+            msg.status.as_u8(),
+            msg.fst_data_byte.as_u8(),
+            msg.snd_data_byte.as_u8(),
+        ));
+        Ok(())
+    }
+}
+
+/// Palette with colors numbered in the order of the configuration, starting from 1.
+pub fn palette() -> Arc<ColorMapping> {
+    let color = |value| DataByte::from_u8(value).unwrap();
+    Arc::new(ColorMapping {
+        ok: color(1),
+        action_triggerred: color(2),
+        transient_error: color(3),
+        not_ok: color(4),
+        initial: color(5),
+        paused: color(6),
+    })
+}
@@ -0,0 +1,5 @@
+pub mod certificates;
+pub mod fixtures;
+pub mod http_stub;
+pub mod kube_stub;
+pub mod midi_stub;
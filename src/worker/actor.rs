@@ -1,7 +1,13 @@
+use std::cell::RefCell;
 use std::future::Future;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use k8s_openapi::chrono::{SecondsFormat, Utc};
+
+use tracing::Instrument;
+
+use crate::metrics::PadMetrics;
 use crate::midi::model::{DataByte, MidiMessage, MidiSender, Status};
 use crate::midi_model::MidiReceiver;
 
@@ -21,6 +27,27 @@ pub struct PadId {
     pub fst_data_byte: DataByte,
 }
 
+/// Formatted as `0x90/0x24`.
+impl std::fmt::Display for PadId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:#04X}/{:#04X}",
+            self.status.as_u8(),
+            self.fst_data_byte.as_u8()
+        )
+    }
+}
+
+impl PadId {
+    pub fn from_message(msg: &MidiMessage) -> PadId {
+        PadId {
+            status: msg.status,
+            fst_data_byte: msg.fst_data_byte,
+        }
+    }
+}
+
 pub struct PadMapping {
     pub pad_id: PadId,
     pub color_mapping: Arc<ColorMapping>,
@@ -64,6 +91,7 @@ impl PadMapping {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum PadOutput {
     Ok,
     NotOk,
@@ -71,9 +99,82 @@ pub enum PadOutput {
     Custom(DataByte),
 }
 
+/// Serialized in the notation of the configuration, e.g. `not_ok` or `114`.
+impl serde::Serialize for PadOutput {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            PadOutput::Ok => serializer.serialize_str("ok"),
+            PadOutput::NotOk => serializer.serialize_str("not_ok"),
+            PadOutput::TempError => serializer.serialize_str("transient_error"),
+            PadOutput::Custom(color) => serializer.serialize_u8(color.as_u8()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamedOutput {
+    Ok,
+    NotOk,
+    TransientError,
+}
+
+/// Output chosen in the configuration, either from the palette (`ok`, `not_ok`, `transient_error`) or as a custom color.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(untagged)]
+pub enum OutputConfig {
+    Named(NamedOutput),
+    Color(DataByte),
+}
+
+impl From<OutputConfig> for PadOutput {
+    fn from(config: OutputConfig) -> Self {
+        match config {
+            OutputConfig::Named(NamedOutput::Ok) => PadOutput::Ok,
+            OutputConfig::Named(NamedOutput::NotOk) => PadOutput::NotOk,
+            OutputConfig::Named(NamedOutput::TransientError) => PadOutput::TempError,
+            OutputConfig::Color(color) => PadOutput::Custom(color),
+        }
+    }
+}
+
+/// Result of passing the pad press to the handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PressOutcome {
+    /// Press pauses or resumes the pad.
+    Ignored,
+    /// Handler has acted on the press. Pad is refreshed immediately.
+    Handled,
+}
+
 #[async_trait::async_trait]
 pub trait PadHandler: Send + Sync {
     async fn handle(&mut self) -> PadOutput;
+
+    /// Details of the last output, e.g. the number of unhealthy workloads, reported by the status API.
+    fn detail(&self) -> Option<String> {
+        None
+    }
+
+    /// Called when the pad is pressed.
+    async fn pressed(&mut self) -> PressOutcome {
+        PressOutcome::Ignored
+    }
+}
+
+#[async_trait::async_trait]
+impl<H: PadHandler + ?Sized> PadHandler for Box<H> {
+    async fn handle(&mut self) -> PadOutput {
+        (**self).handle().await
+    }
+
+    fn detail(&self) -> Option<String> {
+        (**self).detail()
+    }
+
+    async fn pressed(&mut self) -> PressOutcome {
+        (**self).pressed().await
+    }
 }
 
 pub trait Runtime: Send + Sync + 'static {
@@ -132,83 +233,404 @@ impl Runtime for TokioRuntime {
     }
 }
 
-pub trait ColorMappingProvider {
-    fn provide_mapping(&self, status: &Status, fst_db: &DataByte) -> &ColorMapping;
+pub(crate) enum Command {
+    /// Scheduled invocation of the handler. Invocations scheduled before the pad was resumed carry stale generation.
+    TriggerHandler(u64),
+    PadPressed,
+    /// Invokes the handler out of schedule.
+    Refresh,
+    /// Output emitted by the event source.
+    Output(PadOutput),
+    /// Displays the pad as initial, until the event source emits next output.
+    Reset,
+    Pause,
+    Resume,
 }
 
-enum Command {
-    TriggerHandler,
-    PadPressed,
+/// Source of pad outputs driven by events, e.g. Kubernetes watches, instead of periodic invocations.
+#[async_trait::async_trait]
+pub trait PadEventSource: Send {
+    /// Runs for the lifetime of the actor, emitting output whenever the state changes.
+    async fn run(self: Box<Self>, sink: PadOutputSink);
+}
+
+/// Passes outputs of the event source to its actor.
+#[derive(Clone)]
+pub struct PadOutputSink(async_channel::Sender<Command>);
+
+impl PadOutputSink {
+    pub fn emit(&self, output: PadOutput) {
+        let _ = self.0.try_send(Command::Output(output));
+    }
+
+    pub fn reset(&self) {
+        let _ = self.0.try_send(Command::Reset);
+    }
+
+    /// Sink not attached to any actor, together with the queue receiving its commands.
+    #[cfg(test)]
+    pub fn detached() -> (PadOutputSink, async_channel::Receiver<Command>) {
+        let (sender, receiver) = async_channel::unbounded();
+        (PadOutputSink(sender), receiver)
+    }
 }
 
 pub struct Config {
     pub pad_mapping: PadMapping,
     pub schedule_every: Duration,
+    pub metrics: PadMetrics,
+    /// Span of all events of the pad.
+    pub span: tracing::Span,
+    /// Notified about outputs displayed by the pad, e.g. by the bank of the pad.
+    pub observer: Option<Arc<dyn PadOutputObserver>>,
+}
+
+/// Tracks outputs of pads, independently of colors they are displayed with.
+pub trait PadOutputObserver: Send + Sync {
+    /// Output is missing while the pad is paused or waits for its first output.
+    fn output_changed(&self, pad_id: &PadId, output: Option<&PadOutput>);
 }
 
 pub struct ActorHandle(pub tokio::task::JoinHandle<()>);
 
+/// Observed state of the pad.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct PadState {
+    pub paused: bool,
+    pub last_output: Option<PadOutput>,
+    /// Start of the last handler invocation, in RFC 3339 format.
+    pub last_run_at: Option<String>,
+    pub last_run_millis: Option<u64>,
+    /// Time of the last transient error, in RFC 3339 format.
+    pub last_error_at: Option<String>,
+    /// Warning logged by the handler at that time.
+    pub last_error: Option<String>,
+    /// Details of the last output provided by the handler.
+    pub detail: Option<String>,
+}
+
+tokio::task_local! {
+    /// Last warning logged during the running handler invocation.
+    static INVOCATION_WARNING: RefCell<Option<String>>;
+}
+
+/// Layer recording warnings logged by handlers, so that they are reported as the last error of the pad.
+pub struct WarningCapture;
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for WarningCapture {
+    fn on_event(
+        &self,
+        event: &tracing::Event<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        if *event.metadata().level() > tracing::Level::WARN {
+            return;
+        }
+        let _ = INVOCATION_WARNING.try_with(|warning| {
+            let mut visitor = WarningVisitor::default();
+            event.record(&mut visitor);
+            *warning.borrow_mut() = Some(match visitor.error {
+                Some(error) => format!("{} {error}", visitor.message),
+                None => visitor.message,
+            });
+        });
+    }
+}
+
+#[derive(Default)]
+struct WarningVisitor {
+    message: String,
+    error: Option<String>,
+}
+
+impl tracing::field::Visit for WarningVisitor {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{value:?}"),
+            "error" => self.error = Some(format!("{value:?}")),
+            _ => {}
+        }
+    }
+}
+
+/// Controls the pad without its MIDI controller, e.g. through the status API.
+#[derive(Clone)]
+pub struct PadControl {
+    sender: async_channel::Sender<Command>,
+    state: Arc<Mutex<PadState>>,
+}
+
+impl PadControl {
+    pub fn state(&self) -> PadState {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub fn pause(&self) {
+        let _ = self.sender.try_send(Command::Pause);
+    }
+
+    pub fn resume(&self) {
+        let _ = self.sender.try_send(Command::Resume);
+    }
+
+    /// Invokes the handler out of schedule. Paused pads and event sources aren't affected.
+    pub fn trigger(&self) {
+        let _ = self.sender.try_send(Command::Refresh);
+    }
+}
+
+/// Aborts the task, when the owning actor is stopped.
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 #[derive(PartialEq, Eq)]
 enum ActorStatus {
     Running,
     Stopped,
 }
 
+enum Producer {
+    Handler(Arc<tokio::sync::Mutex<dyn PadHandler>>),
+    Source(Box<dyn PadEventSource>),
+}
+
+enum Driver {
+    Handler(Arc<tokio::sync::Mutex<dyn PadHandler>>),
+    /// Output emitted last by the event source. It is displayed again when the pad is resumed.
+    Source(Option<PadOutput>),
+}
+
 struct ActorCtx {
-    handler: Arc<tokio::sync::Mutex<dyn PadHandler>>,
+    driver: Driver,
     midi_sender: Arc<dyn MidiSender + Send + Sync>,
     pad_mapping: PadMapping,
     status: ActorStatus,
+    state: Arc<Mutex<PadState>>,
+    metrics: PadMetrics,
+    observer: Option<Arc<dyn PadOutputObserver>>,
+    /// Generation of the schedule chain, incremented whenever the pad is resumed.
+    generation: u64,
+}
+
+/// Series of the stopped pad would be reported forever, e.g. after the pad is removed by reload.
+impl Drop for ActorCtx {
+    fn drop(&mut self) {
+        self.metrics.remove();
+    }
 }
 
 impl ActorCtx {
-    async fn handle(&mut self, command: Command) {
+    /// Handles the command. Returns whether next handler invocation should be scheduled.
+    async fn handle(&mut self, command: Command) -> bool {
+        if let Command::PadPressed = command {
+            tracing::debug!("Pad pressed.");
+            self.metrics.pressed();
+        }
         match (&self.status, command) {
-            (ActorStatus::Running, Command::TriggerHandler) => {
-                // Send action triggered message. That will signal that action has been initiated.
-                let pending_msg = self.pad_mapping.action_triggerred_message();
-                self.midi_sender.send_and_forget(pending_msg);
-
-                // Send message based on handler output.
-                let mut lock = self.handler.lock().await;
-                let result_msg = match lock.handle().await {
-                    PadOutput::Ok => self.pad_mapping.ok_message(),
-                    PadOutput::NotOk => self.pad_mapping.not_ok_message(),
-                    PadOutput::TempError => self.pad_mapping.transient_error_message(),
-                    PadOutput::Custom(message) => self.pad_mapping.custom_message(message),
-                };
-                self.midi_sender.send_and_forget(result_msg);
+            (ActorStatus::Running, Command::TriggerHandler(generation))
+                if generation == self.generation =>
+            {
+                self.trigger_handler().await;
+                true
             }
             (ActorStatus::Running, Command::PadPressed) => {
-                self.midi_sender
-                    .send_and_forget(self.pad_mapping.paused_message());
-                self.status = ActorStatus::Stopped
+                if let Driver::Handler(handler) = &self.driver {
+                    if handler.lock().await.pressed().await == PressOutcome::Handled {
+                        // Invocation is already scheduled, so pad is only refreshed.
+                        self.trigger_handler().await;
+                        return false;
+                    }
+                }
+                self.pause();
+                false
             }
-            (ActorStatus::Stopped, Command::PadPressed) => {
-                self.midi_sender
-                    .send_and_forget(self.pad_mapping.initial_message());
-                self.status = ActorStatus::Running
+            (ActorStatus::Running, Command::Pause) => {
+                self.pause();
+                false
             }
-            _ => {}
+            (ActorStatus::Running, Command::Refresh) => {
+                self.trigger_handler().await;
+                false
+            }
+            (status, Command::Output(output)) => {
+                if *status == ActorStatus::Running {
+                    self.display(&output);
+                }
+                self.state.lock().unwrap().last_output = Some(output.clone());
+                self.driver = Driver::Source(Some(output));
+                false
+            }
+            (status, Command::Reset) => {
+                if *status == ActorStatus::Running {
+                    self.midi_sender
+                        .send_and_forget(self.pad_mapping.initial_message());
+                    self.observe(None);
+                }
+                self.state.lock().unwrap().last_output = None;
+                self.driver = Driver::Source(None);
+                false
+            }
+            (ActorStatus::Stopped, Command::PadPressed | Command::Resume) => self.resume(),
+            _ => false,
+        }
+    }
+
+    fn pause(&mut self) {
+        tracing::info!("Pad paused.");
+        self.midi_sender
+            .send_and_forget(self.pad_mapping.paused_message());
+        self.observe(None);
+        self.status = ActorStatus::Stopped;
+        self.state.lock().unwrap().paused = true;
+        self.metrics.paused(true);
+    }
+
+    /// Returns whether next handler invocation should be scheduled.
+    fn resume(&mut self) -> bool {
+        tracing::info!("Pad resumed.");
+        self.midi_sender
+            .send_and_forget(self.pad_mapping.initial_message());
+        self.status = ActorStatus::Running;
+        self.state.lock().unwrap().paused = false;
+        self.metrics.paused(false);
+        match &self.driver {
+            Driver::Handler(_) => {
+                // Invocation scheduled before the pause must not start another chain.
+                self.generation += 1;
+                true
+            }
+            Driver::Source(last_output) => {
+                if let Some(output) = last_output.clone() {
+                    self.display(&output);
+                }
+                false
+            }
+        }
+    }
+
+    async fn trigger_handler(&mut self) {
+        let Driver::Handler(handler) = &self.driver else {
+            return;
+        };
+
+        // Send action triggered message. That will signal that action has been initiated.
+        let pending_msg = self.pad_mapping.action_triggerred_message();
+        self.midi_sender.send_and_forget(pending_msg);
+
+        // Send message based on handler output.
+        let started_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let started = Instant::now();
+        let invocation = async {
+            let mut handler = handler.lock().await;
+            let output = handler.handle().await;
+            let warning = INVOCATION_WARNING.with(|warning| warning.take());
+            (output, warning, handler.detail())
+        };
+        let (output, warning, detail) = INVOCATION_WARNING
+            .scope(RefCell::new(None), invocation)
+            .instrument(tracing::debug_span!("invocation"))
+            .await;
+        self.display(&output);
+
+        let elapsed = started.elapsed();
+        self.metrics.invoked(&output, elapsed);
+
+        let mut state = self.state.lock().unwrap();
+        tracing::debug!(outcome = ?output, millis = elapsed.as_millis() as u64, "Handler invoked.");
+        if state.last_output.as_ref() != Some(&output) {
+            tracing::info!(outcome = ?output, previous = ?state.last_output, "Pad output changed.");
+        }
+        if output == PadOutput::TempError || warning.is_some() {
+            state.last_error_at = Some(started_at.clone());
+            state.last_error = warning;
+        }
+        state.last_run_at = Some(started_at);
+        state.last_run_millis = Some(elapsed.as_millis() as u64);
+        state.detail = detail;
+        state.last_output = Some(output);
+    }
+
+    fn display(&self, output: &PadOutput) {
+        self.midi_sender
+            .send_and_forget(self.output_message(output));
+        self.observe(Some(output));
+    }
+
+    fn observe(&self, output: Option<&PadOutput>) {
+        if let Some(observer) = &self.observer {
+            observer.output_changed(&self.pad_mapping.pad_id, output);
+        }
+    }
+
+    fn output_message(&self, output: &PadOutput) -> MidiMessage {
+        match output {
+            PadOutput::Ok => self.pad_mapping.ok_message(),
+            PadOutput::NotOk => self.pad_mapping.not_ok_message(),
+            PadOutput::TempError => self.pad_mapping.transient_error_message(),
+            PadOutput::Custom(message) => self.pad_mapping.custom_message(*message),
         }
     }
 }
 
 pub struct PadActor {
     sender: async_channel::Sender<Command>,
+    state: Arc<Mutex<PadState>>,
     pub pad_id: PadId,
 }
 
-impl PadActor {
-    async fn send_pad_pressed(&self) {
+#[async_trait::async_trait]
+pub trait PadPressedListener: Send + Sync {
+    async fn pad_pressed(&self);
+}
+
+#[async_trait::async_trait]
+impl PadPressedListener for PadActor {
+    async fn pad_pressed(&self) {
         let _ = self.sender.send(Command::PadPressed).await;
     }
+}
+
+impl PadActor {
+    pub fn control(&self) -> PadControl {
+        PadControl {
+            sender: self.sender.clone(),
+            state: self.state.clone(),
+        }
+    }
 
     pub fn start<R: Runtime>(
         handler: Arc<tokio::sync::Mutex<dyn PadHandler>>,
         midi_sender: Arc<dyn MidiSender + Send + Sync>,
         runtime: Arc<R>,
         config: Config,
+    ) -> (ActorHandle, PadActor) {
+        Self::spawn(Producer::Handler(handler), midi_sender, runtime, config)
+    }
+
+    /// Starts actor displaying outputs of the event source. Source isn't invoked periodically.
+    pub fn start_source<R: Runtime>(
+        source: Box<dyn PadEventSource>,
+        midi_sender: Arc<dyn MidiSender + Send + Sync>,
+        runtime: Arc<R>,
+        config: Config,
+    ) -> (ActorHandle, PadActor) {
+        Self::spawn(Producer::Source(source), midi_sender, runtime, config)
+    }
+
+    fn spawn<R: Runtime>(
+        producer: Producer,
+        midi_sender: Arc<dyn MidiSender + Send + Sync>,
+        runtime: Arc<R>,
+        config: Config,
     ) -> (ActorHandle, PadActor) {
         let (sender, msg_queue_receiver) = async_channel::unbounded::<Command>();
 
@@ -217,55 +639,97 @@ impl PadActor {
         let pad_id = config.pad_mapping.pad_id.clone();
         midi_sender.send_and_forget(initial_msg);
 
+        let state: Arc<Mutex<PadState>> = Default::default();
+        let actor_state = state.clone();
         let shared_queue_sender = Arc::new(sender.clone());
-        let running_loop = runtime.clone().spawn(async move {
-            // Send first message to initiate actor.
-            let _ = shared_queue_sender.send(Command::TriggerHandler).await;
-
-            let mut actor_ctx = ActorCtx {
-                handler,
-                midi_sender: midi_sender.clone(),
-                pad_mapping: config.pad_mapping,
-                status: ActorStatus::Running,
-            };
-
-            while let Ok(cmd) = msg_queue_receiver.recv().await {
-                actor_ctx.handle(cmd).await;
-
-                if actor_ctx.status == ActorStatus::Running {
-                    let loop_queue_sender = shared_queue_sender.clone();
-                    runtime.schedule_once(config.schedule_every, async move {
-                        loop_queue_sender.send(Command::TriggerHandler).await?;
-                        Ok(())
-                    });
+        let span = config.span.clone();
+        let running_loop = runtime.clone().spawn(
+            async move {
+                // Event source stops together with the actor.
+                let mut _source_task = None;
+                let driver = match producer {
+                    Producer::Handler(handler) => {
+                        // Send first message to initiate actor.
+                        let _ = shared_queue_sender.send(Command::TriggerHandler(0)).await;
+                        Driver::Handler(handler)
+                    }
+                    Producer::Source(source) => {
+                        let sink = PadOutputSink(shared_queue_sender.as_ref().clone());
+                        let run = source.run(sink).in_current_span();
+                        _source_task = Some(AbortOnDrop(runtime.spawn(run)));
+                        Driver::Source(None)
+                    }
+                };
+
+                let mut actor_ctx = ActorCtx {
+                    driver,
+                    midi_sender: midi_sender.clone(),
+                    pad_mapping: config.pad_mapping,
+                    status: ActorStatus::Running,
+                    state: actor_state,
+                    metrics: config.metrics,
+                    observer: config.observer,
+                    generation: 0,
+                };
+                actor_ctx.metrics.paused(false);
+
+                while let Ok(cmd) = msg_queue_receiver.recv().await {
+                    if actor_ctx.handle(cmd).await {
+                        let loop_queue_sender = shared_queue_sender.clone();
+                        let generation = actor_ctx.generation;
+                        runtime.schedule_once(config.schedule_every, async move {
+                            loop_queue_sender
+                                .send(Command::TriggerHandler(generation))
+                                .await?;
+                            Ok(())
+                        });
+                    }
                 }
             }
-        });
-
-        let actor = PadActor { sender, pad_id };
+            .instrument(span),
+        );
+
+        let actor = PadActor {
+            sender,
+            state,
+            pad_id,
+        };
         let actor_handle = ActorHandle(running_loop);
         (actor_handle, actor)
     }
 }
 
+/// Pad of the particular controller, as the same `PadId` can be used by several controllers.
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+pub struct ScopedPadId {
+    pub controller: String,
+    pub pad_id: PadId,
+}
+
+/// Routes presses of pads of all controllers to their listeners.
+#[derive(Clone, Default)]
 pub struct PadChangesListener {
-    registered: Arc<dashmap::DashMap<PadId, Arc<PadActor>>>,
+    registered: Arc<dashmap::DashMap<ScopedPadId, Arc<dyn PadPressedListener>>>,
 }
 
 impl PadChangesListener {
-    pub fn register(&self, pad_id: PadId, actor: Arc<PadActor>) {
-        let _ = self.registered.insert(pad_id, actor);
+    pub fn register(&self, pad_id: ScopedPadId, listener: Arc<dyn PadPressedListener>) {
+        let _ = self.registered.insert(pad_id, listener);
     }
 
-    pub fn start<MR, RT>(midi_receiver: MR, runtime: Arc<RT>) -> (ActorHandle, PadChangesListener)
+    /// Listens to presses of pads of the controller.
+    pub fn start<MR, RT>(
+        &self,
+        controller: &str,
+        midi_receiver: MR,
+        runtime: Arc<RT>,
+    ) -> ActorHandle
     where
         MR: MidiReceiver + Send + Sync + 'static,
         RT: Runtime,
     {
-        let registered: Arc<dashmap::DashMap<PadId, Arc<PadActor>>> =
-            Arc::new(dashmap::DashMap::new());
-
-        let loop_registered = registered.clone();
+        let controller = controller.to_string();
+        let loop_registered = self.registered.clone();
         let running_loop = runtime.clone().spawn(async move {
             let loop_mr = Arc::new(midi_receiver);
 
@@ -273,22 +737,228 @@ impl PadChangesListener {
                 let iteration_mr = loop_mr.clone();
                 runtime.spawn_blocking(move || iteration_mr.poll()).await
             } {
-                let pad_id = PadId {
-                    status: msg.status,
-                    fst_data_byte: msg.fst_data_byte,
+                let pad_id = ScopedPadId {
+                    controller: controller.clone(),
+                    pad_id: PadId::from_message(&msg),
                 };
 
-                if let Some(actor) = loop_registered.get(&pad_id) {
-                    if msg.snd_data_byte == unsafe { DataByte::from_u8_unsafe(127) } {
-                        let pad_actor: &Arc<PadActor> = actor.value();
-                        pad_actor.send_pad_pressed().await;
+                let listener = loop_registered.get(&pad_id).map(|l| l.value().clone());
+                if let Some(listener) = listener {
+                    if msg.is_pressed() {
+                        listener.pad_pressed().await;
                     };
                 }
             }
         });
 
-        let actor = PadChangesListener { registered };
-        let handle = ActorHandle(running_loop);
-        (handle, actor)
+        ActorHandle(running_loop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::testing::midi_stub::{palette, RecordingSender};
+
+    use super::*;
+
+    struct Counting(Arc<AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl PadHandler for Counting {
+        async fn handle(&mut self) -> PadOutput {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            PadOutput::Ok
+        }
+    }
+
+    struct Unreachable;
+
+    #[async_trait::async_trait]
+    impl PadHandler for Unreachable {
+        async fn handle(&mut self) -> PadOutput {
+            tracing::warn!(error = "connection refused", "Query failed.");
+            PadOutput::TempError
+        }
+
+        fn detail(&self) -> Option<String> {
+            Some("3 attempts".to_string())
+        }
+    }
+
+    fn pad_mapping() -> PadMapping {
+        PadMapping {
+            pad_id: PadId {
+                status: Status::from_u8(0x90).unwrap(),
+                fst_data_byte: DataByte::from_u8(0x24).unwrap(),
+            },
+            color_mapping: palette(),
+        }
+    }
+
+    #[tokio::test]
+    async fn logged_warning_and_detail_are_reported_in_state() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let _subscriber =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(WarningCapture));
+        let pad_mapping = pad_mapping();
+        let state: Arc<Mutex<PadState>> = Default::default();
+        let mut ctx = ActorCtx {
+            driver: Driver::Handler(Arc::new(tokio::sync::Mutex::new(Unreachable))),
+            midi_sender: Arc::new(RecordingSender::default()),
+            metrics: PadMetrics::new("unreachable", None, &pad_mapping.pad_id),
+            pad_mapping,
+            status: ActorStatus::Running,
+            state: state.clone(),
+            observer: None,
+            generation: 0,
+        };
+
+        ctx.handle(Command::TriggerHandler(0)).await;
+
+        let state = state.lock().unwrap().clone();
+        assert_eq!(state.last_output, Some(PadOutput::TempError));
+        assert_eq!(
+            state.last_error.as_deref(),
+            Some("Query failed. connection refused")
+        );
+        assert!(state.last_error_at.is_some());
+        assert_eq!(state.detail.as_deref(), Some("3 attempts"));
+    }
+
+    #[test]
+    fn stopped_pad_removes_its_metrics() {
+        let tokio_runtime = Arc::new(
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .unwrap(),
+        );
+        let runtime = Arc::new(TokioRuntime::new(tokio_runtime.clone()));
+        tokio_runtime.block_on(async move {
+            let invocations = Arc::new(AtomicUsize::new(0));
+            let pad_mapping = pad_mapping();
+            let (handle, actor) = PadActor::start(
+                Arc::new(tokio::sync::Mutex::new(Counting(invocations.clone()))),
+                Arc::new(RecordingSender::default()),
+                runtime,
+                Config {
+                    metrics: PadMetrics::new("reloaded", None, &pad_mapping.pad_id),
+                    pad_mapping,
+                    schedule_every: Duration::from_secs(60),
+                    span: tracing::Span::none(),
+                    observer: None,
+                },
+            );
+            actor.control().pause();
+            while !actor.control().state().paused {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            assert!(crate::metrics::render().contains(
+                r#"rust_midi_pad_paused{bank="",controller="reloaded",pad="0x90/0x24"} 1"#
+            ));
+
+            handle.0.abort();
+            let _ = handle.0.await;
+
+            assert!(!crate::metrics::render().contains(r#"controller="reloaded""#));
+        });
+    }
+
+    #[tokio::test]
+    async fn resuming_pad_does_not_start_another_schedule() {
+        let runtime = Arc::new(ManualRuntime::default());
+        let invocations = Arc::new(AtomicUsize::new(0));
+        let pad_mapping = pad_mapping();
+        let (_handle, actor) = PadActor::start(
+            Arc::new(tokio::sync::Mutex::new(Counting(invocations.clone()))),
+            Arc::new(RecordingSender::default()),
+            runtime.clone(),
+            Config {
+                metrics: PadMetrics::new("resumed", None, &pad_mapping.pad_id),
+                pad_mapping,
+                schedule_every: Duration::from_secs(60),
+                span: tracing::Span::none(),
+                observer: None,
+            },
+        );
+        until(|| runtime.pending() == 1).await;
+        assert_eq!(invocations.load(Ordering::SeqCst), 1);
+
+        // Invocation scheduled before the pause is still pending.
+        let control = actor.control();
+        control.pause();
+        control.resume();
+        until(|| runtime.pending() == 2).await;
+
+        runtime.fire().await;
+        until(|| runtime.pending() == 1).await;
+        assert_eq!(invocations.load(Ordering::SeqCst), 2);
+
+        runtime.fire().await;
+        until(|| runtime.pending() == 1).await;
+        assert_eq!(invocations.load(Ordering::SeqCst), 3);
+    }
+
+    /// Runtime running scheduled actions only when the test fires them.
+    #[derive(Default)]
+    struct ManualRuntime {
+        scheduled: Mutex<Vec<std::pin::Pin<Box<dyn Future<Output = ()> + Send>>>>,
+    }
+
+    impl ManualRuntime {
+        fn pending(&self) -> usize {
+            self.scheduled.lock().unwrap().len()
+        }
+
+        /// Runs all actions scheduled so far.
+        async fn fire(&self) {
+            let scheduled = std::mem::take(&mut *self.scheduled.lock().unwrap());
+            for action in scheduled {
+                action.await;
+            }
+        }
+    }
+
+    impl Runtime for ManualRuntime {
+        fn spawn<F>(&self, task: F) -> tokio::task::JoinHandle<F::Output>
+        where
+            F: Future + Send + 'static,
+            F::Output: Send + 'static,
+        {
+            tokio::spawn(task)
+        }
+
+        fn spawn_blocking<F, R>(&self, task: F) -> tokio::task::JoinHandle<R>
+        where
+            F: FnOnce() -> R + Send + 'static,
+            R: Send + 'static,
+        {
+            tokio::task::spawn_blocking(task)
+        }
+
+        fn schedule_once<A, F>(&self, _after: Duration, action: F)
+        where
+            A: Send + Sync + 'static,
+            F: Future<Output = anyhow::Result<A>> + Send + Sync + 'static,
+        {
+            self.scheduled.lock().unwrap().push(Box::pin(async move {
+                let _ = action.await;
+            }));
+        }
+    }
+
+    /// Waits until the actor reaches the expected state, failing the test after 5 seconds.
+    async fn until(condition: impl Fn() -> bool) {
+        let reached = async {
+            while !condition() {
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), reached)
+            .await
+            .expect("Actor has not reached the expected state.");
     }
 }
@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use k8s_openapi::chrono::{self, SecondsFormat, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::Url;
+
+use crate::worker::actor::{PadHandler, PadOutput, PressOutcome};
+
+const SEVERITY_LABEL: &str = "severity";
+
+/// Label matcher in Alertmanager notation, e.g. `team="sre"`, `env!=dev` or `service=~"api|web"`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Matcher {
+    pub name: String,
+    pub value: String,
+    pub is_regex: bool,
+    pub is_equal: bool,
+}
+
+impl TryFrom<&str> for Matcher {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let no_operator = || anyhow::anyhow!("Matcher '{value}' has no operator.");
+        let (name, rest) = value.split_at(value.find(['=', '!']).ok_or_else(no_operator)?);
+        let (operator, matched) = ["=~", "!~", "!=", "="]
+            .into_iter()
+            .find_map(|operator| Some((operator, rest.strip_prefix(operator)?)))
+            .ok_or_else(no_operator)?;
+
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Matcher '{value}' has no label name.");
+        }
+        let matched = matched.trim();
+        let matched = match matched
+            .strip_prefix('"')
+            .and_then(|matched| matched.strip_suffix('"'))
+        {
+            Some(quoted) => unescape(quoted),
+            None => matched.to_string(),
+        };
+
+        Ok(Matcher {
+            name: name.to_string(),
+            value: matched,
+            is_regex: operator.ends_with('~'),
+            is_equal: !operator.starts_with('!'),
+        })
+    }
+}
+
+impl Display for Matcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let operator = match (self.is_equal, self.is_regex) {
+            (true, false) => "=",
+            (false, false) => "!=",
+            (true, true) => "=~",
+            (false, true) => "!~",
+        };
+        let escaped = self.value.replace('\\', r"\\").replace('"', r#"\""#);
+        write!(f, "{}{operator}\"{escaped}\"", self.name)
+    }
+}
+
+/// Reverts escaping of `\` and `"` in the quoted value. Other characters are kept as written.
+fn unescape(quoted: &str) -> String {
+    let mut value = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars().peekable();
+    while let Some(char) = chars.next() {
+        match chars.peek() {
+            Some(&escaped @ ('\\' | '"')) if char == '\\' => {
+                value.push(escaped);
+                chars.next();
+            }
+            _ => value.push(char),
+        }
+    }
+    value
+}
+
+pub struct Severity {
+    pub name: String,
+    pub output: PadOutput,
+}
+
+pub struct Config {
+    /// Base URL of Alertmanager, e.g. `http://alertmanager:9093/`.
+    pub url: Url,
+    pub matchers: Vec<Matcher>,
+    pub headers: HeaderMap,
+    pub timeout: Duration,
+    /// Outputs of severities, ordered from the highest one.
+    pub severities: Vec<Severity>,
+    /// Output of alerts with severity not listed in `severities`.
+    pub other_severities: PadOutput,
+    /// Duration of silence created on press. Press pauses the pad, when not set.
+    pub silence_for: Option<Duration>,
+}
+
+#[derive(serde::Deserialize)]
+struct Alert {
+    labels: HashMap<String, String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Silence<'a> {
+    matchers: &'a [Matcher],
+    starts_at: String,
+    ends_at: String,
+    created_by: &'a str,
+    comment: &'a str,
+}
+
+/// Reflects the highest severity of active, not silenced and not inhibited alerts.
+pub struct AlertmanagerHandler {
+    client: reqwest::Client,
+    config: Config,
+}
+
+impl AlertmanagerHandler {
+    pub fn new(config: Config) -> AlertmanagerHandler {
+        AlertmanagerHandler {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    async fn active_alerts(&self) -> anyhow::Result<Vec<Alert>> {
+        let mut query = vec![
+            ("active", "true".to_string()),
+            ("silenced", "false".to_string()),
+            ("inhibited", "false".to_string()),
+        ];
+        query.extend(
+            self.config
+                .matchers
+                .iter()
+                .map(|matcher| ("filter", matcher.to_string())),
+        );
+
+        let alerts = self
+            .client
+            .get(self.config.url.join("api/v2/alerts")?)
+            .query(&query)
+            .headers(self.config.headers.clone())
+            .timeout(self.config.timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(alerts)
+    }
+
+    async fn create_silence(&self, silence_for: Duration) -> anyhow::Result<()> {
+        let starts_at = Utc::now();
+        let ends_at = starts_at + chrono::Duration::from_std(silence_for)?;
+        let silence = Silence {
+            matchers: &self.config.matchers,
+            starts_at: starts_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ends_at: ends_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            created_by: "rust_midi",
+            comment: "Silenced from MIDI controller.",
+        };
+
+        self.client
+            .post(self.config.url.join("api/v2/silences")?)
+            .json(&silence)
+            .headers(self.config.headers.clone())
+            .timeout(self.config.timeout)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn output(&self, alerts: &[Alert]) -> PadOutput {
+        if alerts.is_empty() {
+            return PadOutput::Ok;
+        }
+
+        self.config
+            .severities
+            .iter()
+            .find(|severity| {
+                alerts.iter().any(|alert| {
+                    alert.labels.get(SEVERITY_LABEL).map(String::as_str)
+                        == Some(severity.name.as_str())
+                })
+            })
+            .map_or(self.config.other_severities.clone(), |severity| {
+                severity.output.clone()
+            })
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for AlertmanagerHandler {
+    async fn handle(&mut self) -> PadOutput {
+        match self.active_alerts().await {
+            Ok(alerts) => self.output(&alerts),
+            Err(e) => {
+                tracing::warn!(error = %e, "Alerts can't be listed.");
+                PadOutput::TempError
+            }
+        }
+    }
+
+    async fn pressed(&mut self) -> PressOutcome {
+        match self.config.silence_for {
+            Some(silence_for) => {
+                // Failure is visible on the pad, as alerts remain active.
+                if let Err(e) = self.create_silence(silence_for).await {
+                    tracing::warn!(error = %e, "Silence can't be created.");
+                }
+                PressOutcome::Handled
+            }
+            None => PressOutcome::Ignored,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::StatusCode;
+
+    use crate::midi::model::DataByte;
+    use crate::testing::fixtures::warning;
+    use crate::testing::http_stub::HttpStub;
+
+    use super::*;
+
+    const ALERTS_PATH: &str = "/api/v2/alerts";
+    const SILENCES_PATH: &str = "/api/v2/silences";
+
+    fn handler(stub: &HttpStub, silence_for: Option<Duration>) -> AlertmanagerHandler {
+        AlertmanagerHandler::new(Config {
+            url: Url::parse(&format!("{}/", stub.url())).unwrap(),
+            matchers: vec![Matcher::try_from(r#"team="sre""#).unwrap()],
+            headers: HeaderMap::new(),
+            timeout: Duration::from_secs(1),
+            severities: vec![
+                Severity {
+                    name: "critical".to_string(),
+                    output: PadOutput::NotOk,
+                },
+                Severity {
+                    name: "warning".to_string(),
+                    output: warning(),
+                },
+            ],
+            other_severities: PadOutput::Custom(DataByte::from_u8(0x71).unwrap()),
+            silence_for,
+        })
+    }
+
+    fn alerts(severities: &[&str]) -> String {
+        let alerts: Vec<String> = severities
+            .iter()
+            .map(|severity| format!(r#"{{"labels": {{"severity": "{severity}"}}}}"#))
+            .collect();
+        format!("[{}]", alerts.join(","))
+    }
+
+    #[test]
+    fn parses_matchers() {
+        let matcher = |value| Matcher::try_from(value).unwrap();
+
+        assert_eq!(matcher(r#"team="sre""#).to_string(), r#"team="sre""#);
+        assert_eq!(matcher("env != dev").to_string(), r#"env!="dev""#);
+        assert_eq!(
+            matcher(r#"service=~"api|web""#).to_string(),
+            r#"service=~"api|web""#
+        );
+        assert_eq!(
+            matcher(r#"url!~"https?://.*""#).to_string(),
+            r#"url!~"https?://.*""#
+        );
+        assert!(Matcher::try_from("team").is_err());
+        assert!(Matcher::try_from("=sre").is_err());
+    }
+
+    #[test]
+    fn quotes_matcher_values() {
+        let matcher = Matcher::try_from(r#"summary="Zürich \"eu\" \\ 1""#).unwrap();
+
+        assert_eq!(matcher.value, r#"Zürich "eu" \ 1"#);
+        assert_eq!(matcher.to_string(), r#"summary="Zürich \"eu\" \\ 1""#);
+    }
+
+    #[tokio::test]
+    async fn reflects_highest_severity() {
+        let stub = HttpStub::start().await;
+        let mut handler = handler(&stub, None);
+
+        stub.respond(ALERTS_PATH, StatusCode::OK, &alerts(&[]));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.respond(ALERTS_PATH, StatusCode::OK, &alerts(&["info", "warning"]));
+        assert_eq!(handler.handle().await, warning());
+
+        stub.respond(
+            ALERTS_PATH,
+            StatusCode::OK,
+            &alerts(&["warning", "critical"]),
+        );
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+
+        stub.respond(ALERTS_PATH, StatusCode::OK, &alerts(&["info"]));
+        assert_eq!(
+            handler.handle().await,
+            PadOutput::Custom(DataByte::from_u8(0x71).unwrap())
+        );
+
+        stub.respond(ALERTS_PATH, StatusCode::SERVICE_UNAVAILABLE, "");
+        assert_eq!(handler.handle().await, PadOutput::TempError);
+
+        let request = &stub.requests()[0];
+        assert_eq!(request.query["filter"], r#"team="sre""#);
+        assert_eq!(request.query["silenced"], "false");
+    }
+
+    #[tokio::test]
+    async fn press_creates_silence() {
+        let stub = HttpStub::start().await;
+        let mut handler = handler(&stub, Some(Duration::from_secs(15 * 60)));
+        stub.respond(SILENCES_PATH, StatusCode::OK, r#"{"silenceID": "1"}"#);
+
+        assert_eq!(handler.pressed().await, PressOutcome::Handled);
+
+        let request = &stub.requests()[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, SILENCES_PATH);
+        assert!(request.body.contains(
+            r#""matchers":[{"name":"team","value":"sre","isRegex":false,"isEqual":true}]"#
+        ));
+    }
+
+    #[tokio::test]
+    async fn press_without_silence_is_ignored() {
+        let stub = HttpStub::start().await;
+        let mut handler = handler(&stub, None);
+
+        assert_eq!(handler.pressed().await, PressOutcome::Ignored);
+        assert!(stub.requests().is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::midi::model::{DataByte, MidiMessage, MidiSendFailed, MidiSender};
+use crate::worker::actor::{
+    ColorMapping, PadActor, PadId, PadOutput, PadOutputObserver, PadPressedListener,
+};
+
+struct Bank {
+    selector: PadId,
+    /// Latest color of each pad of the bank, also when the bank is not visible.
+    displayed: HashMap<PadId, DataByte>,
+    /// Latest output of each running pad of the bank.
+    outputs: HashMap<PadId, PadOutput>,
+    aggregate: DataByte,
+}
+
+struct BanksState {
+    active: usize,
+    banks: Vec<Bank>,
+}
+
+/// Keeps track of banks of a single controller.
+/// Pads of all banks are updated in the background, but only pads of the active bank are sent to the controller.
+/// Selector pad of each bank displays the most severe output of its pads: `not_ok`, custom color, `transient_error`,
+/// and `ok` in this order. Paused pads and pads without output yet are not considered.
+pub struct BankSwitcher {
+    midi_sender: Arc<dyn MidiSender + Send + Sync>,
+    color_mapping: Arc<ColorMapping>,
+    state: Mutex<BanksState>,
+}
+
+impl BankSwitcher {
+    pub fn new(
+        midi_sender: Arc<dyn MidiSender + Send + Sync>,
+        color_mapping: Arc<ColorMapping>,
+        selectors: Vec<PadId>,
+    ) -> Arc<BankSwitcher> {
+        for selector in &selectors {
+            midi_sender.send_and_forget(Self::message(selector, color_mapping.initial));
+        }
+
+        let banks = selectors
+            .into_iter()
+            .map(|selector| Bank {
+                selector,
+                displayed: HashMap::new(),
+                outputs: HashMap::new(),
+                aggregate: color_mapping.initial,
+            })
+            .collect();
+
+        Arc::new(BankSwitcher {
+            midi_sender,
+            color_mapping,
+            state: Mutex::new(BanksState { active: 0, banks }),
+        })
+    }
+
+    /// Sender used by actors of the given bank.
+    pub fn view(self: &Arc<Self>, bank: usize) -> Arc<dyn MidiSender + Send + Sync> {
+        Arc::new(BankView {
+            switcher: self.clone(),
+            bank,
+        })
+    }
+
+    /// Observer of outputs of pads of the given bank.
+    pub fn observer(self: &Arc<Self>, bank: usize) -> Arc<dyn PadOutputObserver> {
+        Arc::new(BankView {
+            switcher: self.clone(),
+            bank,
+        })
+    }
+
+    /// Listener of the selector pad of the given bank.
+    pub fn selector(self: &Arc<Self>, bank: usize) -> Arc<dyn PadPressedListener> {
+        Arc::new(BankSelector {
+            switcher: self.clone(),
+            bank,
+        })
+    }
+
+    /// Listener of the pad mapped in one or more banks. Press is passed to actor of the active bank.
+    pub fn pad(
+        self: &Arc<Self>,
+        actors: HashMap<usize, Arc<PadActor>>,
+    ) -> Arc<dyn PadPressedListener> {
+        Arc::new(BankedPad {
+            switcher: self.clone(),
+            actors,
+        })
+    }
+
+    fn update(&self, bank: usize, msg: MidiMessage) -> Result<(), MidiSendFailed> {
+        let mut state = self.state.lock().unwrap();
+        let is_active = state.active == bank;
+
+        state.banks[bank]
+            .displayed
+            .insert(PadId::from_message(&msg), msg.snd_data_byte);
+
+        if is_active {
+            self.midi_sender.send(msg)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn observe(&self, bank: usize, pad_id: &PadId, output: Option<&PadOutput>) {
+        let mut state = self.state.lock().unwrap();
+        let current_bank = &mut state.banks[bank];
+        match output {
+            Some(output) => current_bank.outputs.insert(pad_id.clone(), output.clone()),
+            None => current_bank.outputs.remove(pad_id),
+        };
+
+        let aggregate = current_bank
+            .outputs
+            .values()
+            .max_by_key(|output| match output {
+                PadOutput::Ok => (0, 0),
+                PadOutput::TempError => (1, 0),
+                PadOutput::Custom(color) => (2, color.as_u8()),
+                PadOutput::NotOk => (3, 0),
+            })
+            .map_or(self.color_mapping.initial, |output| match output {
+                PadOutput::Ok => self.color_mapping.ok,
+                PadOutput::TempError => self.color_mapping.transient_error,
+                PadOutput::Custom(color) => *color,
+                PadOutput::NotOk => self.color_mapping.not_ok,
+            });
+        if current_bank.aggregate != aggregate {
+            current_bank.aggregate = aggregate;
+            self.midi_sender
+                .send_and_forget(Self::message(&current_bank.selector, aggregate));
+        }
+    }
+
+    fn activate(&self, bank: usize) {
+        let mut state = self.state.lock().unwrap();
+        if state.active == bank {
+            return;
+        }
+        state.active = bank;
+
+        let all_pads: HashSet<&PadId> = state
+            .banks
+            .iter()
+            .flat_map(|b| b.displayed.keys())
+            .collect();
+        let active_bank = &state.banks[bank];
+        for pad_id in all_pads {
+            let color = active_bank
+                .displayed
+                .get(pad_id)
+                .copied()
+                .unwrap_or(self.color_mapping.initial);
+            self.midi_sender
+                .send_and_forget(Self::message(pad_id, color));
+        }
+    }
+
+    fn active(&self) -> usize {
+        self.state.lock().unwrap().active
+    }
+
+    fn message(pad_id: &PadId, snd_data_byte: DataByte) -> MidiMessage {
+        MidiMessage {
+            status: pad_id.status,
+            fst_data_byte: pad_id.fst_data_byte,
+            snd_data_byte,
+        }
+    }
+}
+
+struct BankView {
+    switcher: Arc<BankSwitcher>,
+    bank: usize,
+}
+
+impl MidiSender for BankView {
+    fn send(&self, msg: MidiMessage) -> Result<(), MidiSendFailed> {
+        self.switcher.update(self.bank, msg)
+    }
+}
+
+impl PadOutputObserver for BankView {
+    fn output_changed(&self, pad_id: &PadId, output: Option<&PadOutput>) {
+        self.switcher.observe(self.bank, pad_id, output);
+    }
+}
+
+struct BankSelector {
+    switcher: Arc<BankSwitcher>,
+    bank: usize,
+}
+
+#[async_trait::async_trait]
+impl PadPressedListener for BankSelector {
+    async fn pad_pressed(&self) {
+        self.switcher.activate(self.bank);
+    }
+}
+
+struct BankedPad {
+    switcher: Arc<BankSwitcher>,
+    actors: HashMap<usize, Arc<PadActor>>,
+}
+
+#[async_trait::async_trait]
+impl PadPressedListener for BankedPad {
+    async fn pad_pressed(&self) {
+        if let Some(actor) = self.actors.get(&self.switcher.active()) {
+            actor.pad_pressed().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::midi::model::Status;
+    use crate::testing::fixtures::warning;
+    use crate::testing::midi_stub::{palette, RecordingSender};
+
+    use super::*;
+
+    const OK: u8 = 1;
+    const ACTION_TRIGGERRED: u8 = 2;
+    const TRANSIENT_ERROR: u8 = 3;
+    const NOT_OK: u8 = 4;
+    const INITIAL: u8 = 5;
+    const WARNING: u8 = 0x72;
+
+    fn pad(fst_data_byte: u8) -> PadId {
+        PadId {
+            status: Status::from_u8(0x97).unwrap(),
+            fst_data_byte: DataByte::from_u8(fst_data_byte).unwrap(),
+        }
+    }
+
+    fn display(switcher: &Arc<BankSwitcher>, bank: usize, fst_data_byte: u8, color: u8) {
+        switcher.view(bank).send_and_forget(BankSwitcher::message(
+            &pad(fst_data_byte),
+            DataByte::from_u8(color).unwrap(),
+        ));
+    }
+
+    /// Displays the output as the actor of the pad does.
+    fn show(switcher: &Arc<BankSwitcher>, bank: usize, fst_data_byte: u8, output: PadOutput) {
+        let color = match &output {
+            PadOutput::Ok => OK,
+            PadOutput::NotOk => NOT_OK,
+            PadOutput::TempError => TRANSIENT_ERROR,
+            PadOutput::Custom(color) => color.as_u8(),
+        };
+        display(switcher, bank, fst_data_byte, color);
+        switcher
+            .observer(bank)
+            .output_changed(&pad(fst_data_byte), Some(&output));
+    }
+
+    fn switcher() -> (Arc<BankSwitcher>, Arc<RecordingSender>) {
+        let sender = Arc::new(RecordingSender::default());
+        let switcher = BankSwitcher::new(sender.clone(), palette(), vec![pad(0x70), pad(0x71)]);
+        assert_eq!(
+            sender.take(),
+            [(0x97, 0x70, INITIAL), (0x97, 0x71, INITIAL)]
+        );
+        (switcher, sender)
+    }
+
+    #[test]
+    fn hidden_bank_updates_only_its_selector() {
+        let (switcher, sender) = switcher();
+
+        show(&switcher, 0, 0x00, PadOutput::Ok);
+        assert_eq!(sender.take(), [(0x97, 0x00, OK), (0x97, 0x70, OK)]);
+
+        show(&switcher, 1, 0x00, PadOutput::NotOk);
+        assert_eq!(sender.take(), [(0x97, 0x71, NOT_OK)]);
+        show(&switcher, 1, 0x01, PadOutput::Ok);
+        assert!(sender.take().is_empty());
+    }
+
+    #[test]
+    fn selector_displays_most_severe_output() {
+        let (switcher, sender) = switcher();
+        show(&switcher, 0, 0x00, PadOutput::Ok);
+        show(&switcher, 0, 0x01, warning());
+        assert_eq!(
+            sender.take(),
+            [
+                (0x97, 0x00, OK),
+                (0x97, 0x70, OK),
+                (0x97, 0x01, WARNING),
+                (0x97, 0x70, WARNING)
+            ]
+        );
+
+        // Refreshed pad and transient error don't hide the custom color.
+        display(&switcher, 0, 0x01, ACTION_TRIGGERRED);
+        show(&switcher, 0, 0x02, PadOutput::TempError);
+        assert_eq!(
+            sender.take(),
+            [
+                (0x97, 0x01, ACTION_TRIGGERRED),
+                (0x97, 0x02, TRANSIENT_ERROR)
+            ]
+        );
+
+        // Paused pad is not considered.
+        switcher.observer(0).output_changed(&pad(0x01), None);
+        assert_eq!(sender.take(), [(0x97, 0x70, TRANSIENT_ERROR)]);
+        show(&switcher, 0, 0x00, PadOutput::NotOk);
+        assert_eq!(sender.take(), [(0x97, 0x00, NOT_OK), (0x97, 0x70, NOT_OK)]);
+    }
+
+    #[tokio::test]
+    async fn selector_press_displays_its_bank() {
+        let (switcher, sender) = switcher();
+        show(&switcher, 0, 0x00, PadOutput::Ok);
+        show(&switcher, 0, 0x01, PadOutput::NotOk);
+        show(&switcher, 1, 0x00, PadOutput::NotOk);
+        sender.take();
+
+        switcher.selector(1).pad_pressed().await;
+        let mut redisplayed = sender.take();
+        redisplayed.sort();
+        // Pad not mapped in the bank is displayed as initial.
+        assert_eq!(redisplayed, [(0x97, 0x00, NOT_OK), (0x97, 0x01, INITIAL)]);
+
+        // Already active bank is not displayed again.
+        switcher.selector(1).pad_pressed().await;
+        assert!(sender.take().is_empty());
+
+        show(&switcher, 1, 0x00, PadOutput::Ok);
+        assert_eq!(sender.take(), [(0x97, 0x00, OK), (0x97, 0x71, OK)]);
+        show(&switcher, 0, 0x00, PadOutput::NotOk);
+        assert!(sender.take().is_empty());
+    }
+}
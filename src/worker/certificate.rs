@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use openssl::asn1::Asn1Time;
+use openssl::x509::X509;
+use tokio::net::TcpStream;
+use tokio_native_tls::TlsConnector;
+
+use crate::worker::actor::{PadHandler, PadOutput};
+
+pub enum Source {
+    /// PEM file with one or more certificates.
+    File(PathBuf),
+    /// TLS endpoint presenting the certificate.
+    Endpoint {
+        host: String,
+        port: u16,
+        server_name: String,
+        /// Accepts invalid certificates, so that expired ones can be inspected as well.
+        connector: TlsConnector,
+    },
+}
+
+pub struct Config {
+    pub source: Source,
+    pub timeout: Duration,
+    /// Certificate expiring within this number of days is displayed with `warning` output.
+    pub warning_days: i32,
+    /// Certificate expiring within this number of days is not OK.
+    pub critical_days: i32,
+    pub warning: PadOutput,
+}
+
+/// Checks the number of days remaining until the certificate expires.
+pub struct CertificateHandler {
+    config: Config,
+}
+
+impl CertificateHandler {
+    pub fn new(config: Config) -> CertificateHandler {
+        CertificateHandler { config }
+    }
+
+    async fn certificates(&self) -> anyhow::Result<Vec<X509>> {
+        match &self.config.source {
+            Source::File(path) => {
+                let pem = tokio::fs::read(path).await?;
+                Ok(X509::stack_from_pem(&pem)?)
+            }
+            Source::Endpoint {
+                host,
+                port,
+                server_name,
+                connector,
+            } => {
+                let stream = TcpStream::connect((host.as_str(), *port)).await?;
+                let stream = connector.connect(server_name, stream).await?;
+                let certificate = stream
+                    .get_ref()
+                    .peer_certificate()?
+                    .ok_or_else(|| anyhow::anyhow!("{host}:{port} presented no certificate."))?;
+                Ok(vec![X509::from_der(&certificate.to_der()?)?])
+            }
+        }
+    }
+
+    /// Days remaining until the earliest expiry. Negative for expired certificates.
+    async fn days_remaining(&self) -> anyhow::Result<i32> {
+        let certificates = tokio::time::timeout(self.config.timeout, self.certificates()).await??;
+        let now = Asn1Time::days_from_now(0)?;
+
+        certificates
+            .iter()
+            .map(|certificate| Ok(now.diff(certificate.not_after())?.days))
+            .reduce(|a: anyhow::Result<i32>, b| Ok(a?.min(b?)))
+            .unwrap_or_else(|| Err(anyhow::anyhow!("No certificate found.")))
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for CertificateHandler {
+    async fn handle(&mut self) -> PadOutput {
+        match self.days_remaining().await {
+            Ok(days) if days < self.config.critical_days => PadOutput::NotOk,
+            Ok(days) if days <= self.config.warning_days => self.config.warning.clone(),
+            Ok(_) => PadOutput::Ok,
+            Err(e) => {
+                tracing::warn!(error = %e, "Certificate can't be checked.");
+                PadOutput::TempError
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::certificates::{self, TestCertificate};
+    use crate::testing::fixtures::{warning, TempDir};
+
+    use super::*;
+
+    fn handler(source: Source) -> CertificateHandler {
+        CertificateHandler::new(Config {
+            source,
+            timeout: Duration::from_secs(1),
+            warning_days: 30,
+            critical_days: 7,
+            warning: warning(),
+        })
+    }
+
+    async fn check_file(name: &str, certificates: &[TestCertificate]) -> PadOutput {
+        let directory = TempDir::new("certificate");
+        let path = directory.path().join(format!("{name}.pem"));
+        let pem: Vec<u8> = certificates
+            .iter()
+            .flat_map(|certificate| certificate.cert_pem.clone())
+            .collect();
+        std::fs::write(&path, pem).unwrap();
+        handler(Source::File(path)).handle().await
+    }
+
+    #[tokio::test]
+    async fn maps_remaining_days_of_file_certificate() {
+        let valid = certificates::self_signed(60);
+        assert_eq!(check_file("valid", &[valid]).await, PadOutput::Ok);
+
+        let expiring = certificates::self_signed(20);
+        assert_eq!(check_file("expiring", &[expiring]).await, warning());
+
+        let critical = certificates::self_signed(3);
+        assert_eq!(check_file("critical", &[critical]).await, PadOutput::NotOk);
+
+        let expired = certificates::self_signed(-10);
+        assert_eq!(check_file("expired", &[expired]).await, PadOutput::NotOk);
+    }
+
+    #[tokio::test]
+    async fn earliest_expiry_of_chain_wins() {
+        let chain = [certificates::self_signed(60), certificates::self_signed(20)];
+        assert_eq!(check_file("chain", &chain).await, warning());
+    }
+
+    #[tokio::test]
+    async fn unreadable_file_is_temp_error() {
+        let mut handler = handler(Source::File(PathBuf::from("/nonexistent/cert.pem")));
+        assert_eq!(handler.handle().await, PadOutput::TempError);
+
+        assert_eq!(check_file("empty", &[]).await, PadOutput::TempError);
+    }
+
+    #[tokio::test]
+    async fn checks_certificate_of_endpoint() {
+        let endpoint = |address: std::net::SocketAddr| Source::Endpoint {
+            host: address.ip().to_string(),
+            port: address.port(),
+            server_name: "localhost".to_string(),
+            connector: native_tls::TlsConnector::builder()
+                .danger_accept_invalid_certs(true)
+                .build()
+                .unwrap()
+                .into(),
+        };
+
+        let valid = certificates::serve_tls(&certificates::self_signed(60)).await;
+        assert_eq!(handler(endpoint(valid)).handle().await, PadOutput::Ok);
+
+        let expired = certificates::serve_tls(&certificates::self_signed(-1)).await;
+        assert_eq!(handler(endpoint(expired)).handle().await, PadOutput::NotOk);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::worker::actor::{PadHandler, PadOutput, PressOutcome};
+
+/// The way outputs of children are combined into a single output.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Policy {
+    /// OK only when every child is OK. Transient errors of children are propagated.
+    AllOk,
+    /// Not OK when any child is not OK.
+    AnyFailed,
+    /// The most severe output of children.
+    WorstOf,
+    /// OK when more than half of children are OK. Children with transient errors don't vote.
+    Majority,
+}
+
+pub struct CompositeHandler {
+    policy: Policy,
+    children: Vec<Box<dyn PadHandler>>,
+}
+
+impl CompositeHandler {
+    pub fn new(policy: Policy, children: Vec<Box<dyn PadHandler>>) -> CompositeHandler {
+        CompositeHandler { policy, children }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for CompositeHandler {
+    async fn handle(&mut self) -> PadOutput {
+        let outputs =
+            futures::future::join_all(self.children.iter_mut().map(|child| child.handle())).await;
+        combine(self.policy, outputs)
+    }
+
+    /// Press is passed to every child. It is handled, when any child handles it.
+    async fn pressed(&mut self) -> PressOutcome {
+        let outcomes =
+            futures::future::join_all(self.children.iter_mut().map(|child| child.pressed())).await;
+        if outcomes.contains(&PressOutcome::Handled) {
+            PressOutcome::Handled
+        } else {
+            PressOutcome::Ignored
+        }
+    }
+}
+
+fn severity(output: &PadOutput) -> u8 {
+    match output {
+        PadOutput::Ok => 0,
+        PadOutput::Custom(_) => 1,
+        PadOutput::TempError => 2,
+        PadOutput::NotOk => 3,
+    }
+}
+
+fn combine(policy: Policy, outputs: Vec<PadOutput>) -> PadOutput {
+    let is_ok = |output: &PadOutput| matches!(output, PadOutput::Ok);
+    let is_not_ok = |output: &PadOutput| matches!(output, PadOutput::NotOk);
+    let is_temp_error = |output: &PadOutput| matches!(output, PadOutput::TempError);
+
+    match policy {
+        Policy::AllOk if outputs.iter().all(|o| is_ok(o) || is_temp_error(o)) => {
+            if outputs.iter().any(is_temp_error) {
+                PadOutput::TempError
+            } else {
+                PadOutput::Ok
+            }
+        }
+        Policy::AllOk => PadOutput::NotOk,
+        Policy::AnyFailed if outputs.iter().any(is_not_ok) => PadOutput::NotOk,
+        Policy::AnyFailed if outputs.iter().any(is_temp_error) => PadOutput::TempError,
+        Policy::AnyFailed => PadOutput::Ok,
+        Policy::WorstOf => outputs
+            .into_iter()
+            // First of equally severe outputs wins.
+            .rev()
+            .max_by_key(severity)
+            .unwrap_or(PadOutput::Ok),
+        Policy::Majority => {
+            let voting = outputs.iter().filter(|o| !is_temp_error(o)).count();
+            let ok = outputs.iter().filter(|o| is_ok(o)).count();
+            match voting {
+                0 => PadOutput::TempError,
+                _ if ok * 2 > voting => PadOutput::Ok,
+                _ => PadOutput::NotOk,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::fixtures::warning;
+
+    use super::*;
+
+    #[test]
+    fn all_ok_propagates_transient_errors() {
+        use PadOutput::*;
+
+        assert_eq!(combine(Policy::AllOk, vec![Ok, Ok]), Ok);
+        assert_eq!(combine(Policy::AllOk, vec![Ok, TempError]), TempError);
+        assert_eq!(combine(Policy::AllOk, vec![TempError, NotOk]), NotOk);
+        assert_eq!(combine(Policy::AllOk, vec![Ok, warning()]), NotOk);
+    }
+
+    #[test]
+    fn any_failed_ignores_custom_outputs() {
+        use PadOutput::*;
+
+        assert_eq!(combine(Policy::AnyFailed, vec![Ok, warning()]), Ok);
+        assert_eq!(combine(Policy::AnyFailed, vec![TempError, Ok]), TempError);
+        assert_eq!(combine(Policy::AnyFailed, vec![TempError, NotOk]), NotOk);
+    }
+
+    #[test]
+    fn worst_of_picks_most_severe_output() {
+        use PadOutput::*;
+
+        assert_eq!(combine(Policy::WorstOf, vec![Ok, warning()]), warning());
+        assert_eq!(
+            combine(Policy::WorstOf, vec![warning(), TempError]),
+            TempError
+        );
+        assert_eq!(combine(Policy::WorstOf, vec![NotOk, TempError]), NotOk);
+        assert_eq!(combine(Policy::WorstOf, vec![]), Ok);
+    }
+
+    #[test]
+    fn majority_requires_more_than_half_ok() {
+        use PadOutput::*;
+
+        assert_eq!(combine(Policy::Majority, vec![Ok, Ok, NotOk]), Ok);
+        assert_eq!(combine(Policy::Majority, vec![Ok, NotOk]), NotOk);
+        assert_eq!(combine(Policy::Majority, vec![Ok, warning(), NotOk]), NotOk);
+    }
+
+    #[test]
+    fn majority_ignores_transient_errors() {
+        use PadOutput::*;
+
+        assert_eq!(combine(Policy::Majority, vec![Ok, TempError]), Ok);
+        assert_eq!(combine(Policy::Majority, vec![Ok, NotOk, TempError]), NotOk);
+        assert_eq!(
+            combine(Policy::Majority, vec![TempError, TempError]),
+            TempError
+        );
+    }
+}
@@ -0,0 +1,263 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use regex::Regex;
+
+use crate::worker::actor::{PadEventSource, PadHandler, PadOutput, PadOutputSink};
+
+/// Delay of the check after a change, so that a burst of changes causes a single check.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+pub struct Config {
+    /// Glob pattern of checked files, e.g. `/backups/*.marker`.
+    pub pattern: String,
+    /// Newest matched file must be modified within this duration.
+    pub max_age: Option<Duration>,
+    /// Matched files must not contain this pattern.
+    pub contains: Option<Regex>,
+    pub missing: PadOutput,
+    pub stale: PadOutput,
+    pub matched: PadOutput,
+}
+
+enum Finding {
+    Fresh,
+    Missing,
+    Stale,
+    Matched,
+}
+
+/// Checks existence, age and content of files matching the glob pattern.
+pub struct FileHandler {
+    config: Arc<Config>,
+}
+
+impl FileHandler {
+    pub fn new(config: Config) -> FileHandler {
+        FileHandler {
+            config: Arc::new(config),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for FileHandler {
+    async fn handle(&mut self) -> PadOutput {
+        let config = self.config.clone();
+        match tokio::task::spawn_blocking(move || check(&config)).await {
+            Ok(Ok(Finding::Fresh)) => PadOutput::Ok,
+            Ok(Ok(Finding::Missing)) => self.config.missing.clone(),
+            Ok(Ok(Finding::Stale)) => self.config.stale.clone(),
+            Ok(Ok(Finding::Matched)) => self.config.matched.clone(),
+            Ok(Err(e)) => {
+                tracing::warn!(error = %e, "Files can't be checked.");
+                PadOutput::TempError
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "File check failed.");
+                PadOutput::TempError
+            }
+        }
+    }
+}
+
+/// Checks the files as soon as the watched directory changes, and periodically to find stale files.
+pub struct FileWatch {
+    handler: FileHandler,
+    every: Duration,
+}
+
+impl FileWatch {
+    pub fn new(config: Config, every: Duration) -> FileWatch {
+        FileWatch {
+            handler: FileHandler::new(config),
+            every,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadEventSource for FileWatch {
+    async fn run(self: Box<Self>, sink: PadOutputSink) {
+        let FileWatch { mut handler, every } = *self;
+        let (signal, signals) = async_channel::bounded::<()>(1);
+        // Files are still checked periodically, when the directory can't be watched.
+        let _watcher = match watch(&handler.config.pattern, signal) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                tracing::warn!(error = %e, "Directory can't be watched.");
+                None
+            }
+        };
+
+        loop {
+            sink.emit(handler.handle().await);
+            tokio::select! {
+                _ = tokio::time::sleep(every) => {}
+                Ok(()) = signals.recv() => {
+                    tokio::time::sleep(WATCH_DEBOUNCE).await;
+                    let _ = signals.try_recv();
+                }
+            }
+        }
+    }
+}
+
+/// Signals changes in the directory of the pattern, until the watcher is dropped.
+fn watch(pattern: &str, signal: async_channel::Sender<()>) -> notify::Result<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |_| {
+        // Pending signal already covers this change.
+        let _ = signal.try_send(());
+    })?;
+    let (directory, mode) = watched_directory(pattern);
+    watcher.watch(&directory, mode)?;
+    Ok(watcher)
+}
+
+fn check(config: &Config) -> anyhow::Result<Finding> {
+    let files = glob::glob(&config.pattern)?
+        .filter(|entry| entry.as_ref().map_or(true, |path| path.is_file()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    if files.is_empty() {
+        return Ok(Finding::Missing);
+    }
+
+    if let Some(max_age) = config.max_age {
+        let newest = files
+            .iter()
+            .map(|file| Ok(file.metadata()?.modified()?))
+            .reduce(|a: anyhow::Result<SystemTime>, b| Ok(a?.max(b?)))
+            .unwrap_or_else(|| Err(anyhow::anyhow!("No file matched.")))?;
+        // Modification time in the future is considered fresh.
+        if newest.elapsed().unwrap_or_default() > max_age {
+            return Ok(Finding::Stale);
+        }
+    }
+
+    if let Some(contains) = &config.contains {
+        for file in &files {
+            let content = std::fs::read(file)?;
+            if contains.is_match(&String::from_utf8_lossy(&content)) {
+                return Ok(Finding::Matched);
+            }
+        }
+    }
+    Ok(Finding::Fresh)
+}
+
+/// The deepest directory without glob characters, watched recursively when the pattern spans more levels below it.
+fn watched_directory(pattern: &str) -> (PathBuf, RecursiveMode) {
+    let components: Vec<Component> = Path::new(pattern).components().collect();
+    let literal = components
+        .iter()
+        .take_while(|component| {
+            !component
+                .as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '['])
+        })
+        .count()
+        // The last component is a file name, even without glob characters.
+        .min(components.len().saturating_sub(1));
+
+    let directory: PathBuf = components[..literal].iter().collect();
+    let directory = if directory.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        directory
+    };
+    let mode = if components.len() - literal > 1 {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    (directory, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::midi::model::DataByte;
+    use crate::testing::fixtures::{emitted, warning, TempDir};
+
+    use super::*;
+
+    fn config(directory: &Path) -> Config {
+        Config {
+            pattern: format!("{}/*.marker", directory.display()),
+            max_age: Some(Duration::from_secs(60 * 60)),
+            contains: Some(Regex::new("FAILED").unwrap()),
+            missing: PadOutput::NotOk,
+            stale: warning(),
+            matched: PadOutput::Custom(DataByte::from_u8(0x71).unwrap()),
+        }
+    }
+
+    #[test]
+    fn watches_directory_without_glob_characters() {
+        let watched = |pattern| {
+            let (directory, mode) = watched_directory(pattern);
+            (directory.to_string_lossy().to_string(), mode)
+        };
+
+        assert_eq!(
+            watched("/backups/*.marker"),
+            ("/backups".to_string(), RecursiveMode::NonRecursive)
+        );
+        assert_eq!(
+            watched("/backups/db.marker"),
+            ("/backups".to_string(), RecursiveMode::NonRecursive)
+        );
+        assert_eq!(
+            watched("/backups/*/done"),
+            ("/backups".to_string(), RecursiveMode::Recursive)
+        );
+        assert_eq!(
+            watched("done"),
+            (".".to_string(), RecursiveMode::NonRecursive)
+        );
+    }
+
+    #[tokio::test]
+    async fn reflects_matched_files() {
+        let directory = TempDir::new("file-handler");
+        let directory = directory.path();
+        let mut handler = FileHandler::new(config(directory));
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+
+        let marker = directory.join("db.marker");
+        std::fs::write(&marker, "OK").unwrap();
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        std::fs::write(directory.join("files.marker"), "FAILED").unwrap();
+        assert_eq!(
+            handler.handle().await,
+            PadOutput::Custom(DataByte::from_u8(0x71).unwrap())
+        );
+        std::fs::remove_file(directory.join("files.marker")).unwrap();
+
+        let old = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(&marker)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        assert_eq!(handler.handle().await, warning());
+    }
+
+    #[tokio::test]
+    async fn watch_checks_files_on_change() {
+        let directory = TempDir::new("file-watch");
+        let watch = FileWatch::new(config(directory.path()), Duration::from_secs(60 * 60));
+        let (sink, commands) = PadOutputSink::detached();
+        let running = tokio::spawn(Box::new(watch).run(sink));
+        assert_eq!(emitted(&commands).await, PadOutput::NotOk);
+
+        std::fs::write(directory.path().join("db.marker"), "OK").unwrap();
+        assert_eq!(emitted(&commands).await, PadOutput::Ok);
+
+        running.abort();
+    }
+}
@@ -1,17 +1,33 @@
-use crate::kubernetes::model::{DeploymentId, DeploymentStatus, K8sClient};
+use crate::kubernetes::model::{
+    ClusterContext, ClusterHealth, DeploymentId, DeploymentStatus, K8sClient, NamespaceId,
+    ReplicaThresholds,
+};
 use crate::midi::model::DataByte;
-use crate::worker::actor::{PadHandler, PadOutput};
+use crate::worker::actor::{PadEventSource, PadHandler, PadOutput, PadOutputSink};
+use futures::StreamExt;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 pub struct Config {
     pub deployment_id: DeploymentId,
+    pub replicas: ReplicaThresholds,
     pub unknown: DataByte,
     pub depl_in_progress: DataByte,
+    /// Color of partially ready deployment. Displayed as not OK, when not set.
+    pub degraded: Option<DataByte>,
+    /// Whether containers of the deployment's pods are checked for failures and restarts.
+    pub check_pods: bool,
+    /// Color of deployment with crashing containers. Displayed as not OK, when not set.
+    pub crashing: Option<DataByte>,
 }
 
 pub struct K8SDeploymentHandler {
     k8s_client: Arc<dyn K8sClient + Send + Sync>,
     config: Config,
+    /// Restart counts of containers seen by the previous invocation.
+    /// Unknown after the deployment has failed, when containers aren't checked.
+    restarts: Option<HashMap<String, u32>>,
 }
 
 impl K8SDeploymentHandler {
@@ -19,7 +35,37 @@ impl K8SDeploymentHandler {
         k8s_client: Arc<dyn K8sClient + Send + Sync>,
         config: Config,
     ) -> K8SDeploymentHandler {
-        K8SDeploymentHandler { k8s_client, config }
+        K8SDeploymentHandler {
+            k8s_client,
+            config,
+            restarts: Some(HashMap::new()),
+        }
+    }
+
+    /// Checks whether any container is failing or has been restarted since the previous invocation.
+    /// Container seen for the first time counts as restarted, when it was killed for running out of memory.
+    async fn containers_crashing(&mut self) -> anyhow::Result<bool> {
+        let containers = self
+            .k8s_client
+            .deployment_containers(&self.config.deployment_id)
+            .await?;
+
+        let restarts: HashMap<String, u32> = containers
+            .iter()
+            .map(|container| (container.id.clone(), container.restart_count))
+            .collect();
+        let crashing = containers.iter().any(|container| {
+            let restarted = match self.restarts.as_ref().map(|r| r.get(&container.id)) {
+                // Restarts of the failed deployment aren't reported after it recovers.
+                None => false,
+                Some(Some(previous)) => container.restart_count > *previous,
+                Some(None) => container.oom_killed,
+            };
+            container.failure.is_some() || restarted
+        });
+        self.restarts = Some(restarts);
+
+        Ok(crashing)
     }
 }
 
@@ -27,14 +73,699 @@ impl K8SDeploymentHandler {
 impl PadHandler for K8SDeploymentHandler {
     async fn handle(&mut self) -> PadOutput {
         let deployment_id = &self.config.deployment_id;
-        let deployment_status = self.k8s_client.check_deployment(deployment_id).await;
+        let deployment_status = self
+            .k8s_client
+            .check_deployment(deployment_id, &self.config.replicas)
+            .await;
+
+        let status = match deployment_status {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::warn!(error = %e, "Deployment can't be checked.");
+                return PadOutput::TempError;
+            }
+        };
+
+        if self.config.check_pods && status == DeploymentStatus::NonOK {
+            self.restarts = None;
+        } else if self.config.check_pods {
+            match self.containers_crashing().await {
+                Ok(true) => {
+                    return self
+                        .config
+                        .crashing
+                        .map_or(PadOutput::NotOk, PadOutput::Custom)
+                }
+                Ok(false) => (),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Pods can't be checked.");
+                    return PadOutput::TempError;
+                }
+            }
+        }
+
+        status_output(
+            status,
+            self.config.depl_in_progress,
+            self.config.unknown,
+            self.config.degraded,
+        )
+    }
+}
+
+/// Delay of restarting the failed watch.
+const WATCH_RETRY: Duration = Duration::from_secs(5);
+
+/// Watches the deployment and emits its output after every change, instead of periodic checks.
+pub struct K8SDeploymentWatch {
+    k8s_client: Arc<dyn K8sClient + Send + Sync>,
+    config: Config,
+}
+
+impl K8SDeploymentWatch {
+    pub fn new(k8s_client: Arc<dyn K8sClient + Send + Sync>, config: Config) -> K8SDeploymentWatch {
+        K8SDeploymentWatch { k8s_client, config }
+    }
+
+    fn output(&self, status: anyhow::Result<DeploymentStatus>) -> PadOutput {
+        match status {
+            Ok(status) => status_output(
+                status,
+                self.config.depl_in_progress,
+                self.config.unknown,
+                self.config.degraded,
+            ),
+            Err(e) => {
+                tracing::warn!(error = %e, "Watch failed.");
+                PadOutput::TempError
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadEventSource for K8SDeploymentWatch {
+    async fn run(self: Box<Self>, sink: PadOutputSink) {
+        loop {
+            let watch = self
+                .k8s_client
+                .watch_deployment(&self.config.deployment_id, &self.config.replicas)
+                .await;
+            let mut statuses = match watch {
+                Ok(statuses) => statuses,
+                Err(e) => {
+                    sink.emit(self.output(Err(e)));
+                    tokio::time::sleep(WATCH_RETRY).await;
+                    continue;
+                }
+            };
+
+            while let Some(status) = statuses.next().await {
+                let failed = status.is_err();
+                sink.emit(self.output(status));
+                if failed {
+                    tokio::time::sleep(WATCH_RETRY).await;
+                }
+            }
+        }
+    }
+}
+
+pub struct NamespaceConfig {
+    pub namespace_id: NamespaceId,
+    pub label_selector: Option<String>,
+    /// Number of unhealthy workloads which doesn't make the namespace not OK.
+    pub max_unhealthy: usize,
+    pub unknown: DataByte,
+    pub depl_in_progress: DataByte,
+}
+
+pub struct K8SNamespaceHandler {
+    k8s_client: Arc<dyn K8sClient + Send + Sync>,
+    config: NamespaceConfig,
+    /// Number of unhealthy workloads and of all workloads found by the last successful invocation.
+    unhealthy: Option<(usize, usize)>,
+}
+
+impl K8SNamespaceHandler {
+    pub fn new(
+        k8s_client: Arc<dyn K8sClient + Send + Sync>,
+        config: NamespaceConfig,
+    ) -> K8SNamespaceHandler {
+        K8SNamespaceHandler {
+            k8s_client,
+            config,
+            unhealthy: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for K8SNamespaceHandler {
+    async fn handle(&mut self) -> PadOutput {
+        let namespace_status = self
+            .k8s_client
+            .check_namespace(
+                &self.config.namespace_id,
+                self.config.label_selector.as_deref(),
+            )
+            .await;
+
+        match namespace_status {
+            Ok(namespace_status) => {
+                let unhealthy = namespace_status.unhealthy();
+                let workloads = namespace_status.workloads.len();
+                if self.unhealthy.map(|(previous, _)| previous) != Some(unhealthy) {
+                    tracing::info!(
+                        namespace = %self.config.namespace_id,
+                        unhealthy,
+                        workloads,
+                        "Unhealthy workloads changed."
+                    );
+                }
+                self.unhealthy = Some((unhealthy, workloads));
+                let tolerated = unhealthy <= self.config.max_unhealthy;
+                status_output(
+                    namespace_status.worst(tolerated),
+                    self.config.depl_in_progress,
+                    self.config.unknown,
+                    None,
+                )
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Namespace can't be checked.");
+                PadOutput::TempError
+            }
+        }
+    }
+
+    fn detail(&self) -> Option<String> {
+        self.unhealthy
+            .map(|(unhealthy, workloads)| format!("{unhealthy} of {workloads} workloads unhealthy"))
+    }
+}
+
+pub struct EventsConfig {
+    pub workload_id: DeploymentId,
+    /// Reasons of warning events which are counted.
+    pub reasons: Vec<String>,
+    pub window: Duration,
+    /// Number of warnings in the window which is still OK.
+    pub threshold: u32,
+}
+
+/// Counts warning events of the workload in a sliding window.
+/// Events are aggregated by Kubernetes, so new occurrences are detected by the increase of event count.
+/// Only occurrences since the first invocation are counted.
+pub struct K8SEventsHandler {
+    k8s_client: Arc<dyn K8sClient + Send + Sync>,
+    config: EventsConfig,
+    /// Latest count of each event, unknown before the first listing.
+    counts: Option<HashMap<String, u32>>,
+    /// New occurrences found by each invocation.
+    occurrences: VecDeque<(Instant, u32)>,
+}
+
+impl K8SEventsHandler {
+    pub fn new(k8s_client: Arc<dyn K8sClient + Send + Sync>, config: EventsConfig) -> Self {
+        K8SEventsHandler {
+            k8s_client,
+            config,
+            counts: None,
+            occurrences: VecDeque::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for K8SEventsHandler {
+    async fn handle(&mut self) -> PadOutput {
+        let events = match self
+            .k8s_client
+            .warning_events(&self.config.workload_id)
+            .await
+        {
+            Ok(events) => events,
+            Err(e) => {
+                tracing::warn!(error = %e, "Events can't be listed.");
+                return PadOutput::TempError;
+            }
+        };
+
+        let now = Instant::now();
+        let window = self.config.window;
+        let mut counts = HashMap::new();
+        for event in events
+            .into_iter()
+            .filter(|event| self.config.reasons.contains(&event.reason))
+        {
+            let new_occurrences = match self.counts.as_ref().map(|counts| counts.get(&event.uid)) {
+                // First listing only sets the baseline.
+                None => 0,
+                Some(Some(previous)) => event.count.saturating_sub(*previous),
+                // Event created since the previous listing. Count it, only when it is recent.
+                Some(None) if event.age.is_some_and(|age| age <= window) => event.count,
+                Some(None) => 0,
+            };
+            if new_occurrences > 0 {
+                self.occurrences.push_back((now, new_occurrences));
+            }
+            counts.insert(event.uid, event.count);
+        }
+        self.counts = Some(counts);
+
+        while let Some((at, _)) = self.occurrences.front() {
+            if now.duration_since(*at) <= window {
+                break;
+            }
+            self.occurrences.pop_front();
+        }
+
+        let warnings: u32 = self.occurrences.iter().map(|(_, count)| count).sum();
+        if warnings > self.config.threshold {
+            PadOutput::NotOk
+        } else {
+            PadOutput::Ok
+        }
+    }
+}
+
+pub enum ClusterCheck {
+    Nodes,
+    ApiServer,
+}
+
+pub struct ClusterConfig {
+    pub context: ClusterContext,
+    pub check: ClusterCheck,
+    pub degraded: DataByte,
+}
+
+pub struct K8SClusterHandler {
+    k8s_client: Arc<dyn K8sClient + Send + Sync>,
+    config: ClusterConfig,
+}
+
+impl K8SClusterHandler {
+    pub fn new(
+        k8s_client: Arc<dyn K8sClient + Send + Sync>,
+        config: ClusterConfig,
+    ) -> K8SClusterHandler {
+        K8SClusterHandler { k8s_client, config }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for K8SClusterHandler {
+    async fn handle(&mut self) -> PadOutput {
+        let context = &self.config.context;
+        let cluster_health = match self.config.check {
+            ClusterCheck::Nodes => self.k8s_client.check_nodes(context).await,
+            ClusterCheck::ApiServer => self.k8s_client.check_api_server(context).await,
+        };
 
-        match deployment_status {
-            Ok(DeploymentStatus::OK) => PadOutput::Ok,
-            Ok(DeploymentStatus::NonOK) => PadOutput::NotOk,
-            Ok(DeploymentStatus::InProgress) => PadOutput::Custom(self.config.depl_in_progress),
-            Ok(DeploymentStatus::Unknown) => PadOutput::Custom(self.config.unknown),
-            Err(_) => PadOutput::TempError,
+        match cluster_health {
+            Ok(ClusterHealth::Healthy) => PadOutput::Ok,
+            Ok(ClusterHealth::Degraded) => PadOutput::Custom(self.config.degraded),
+            Ok(ClusterHealth::Down) => PadOutput::NotOk,
+            Err(e) => {
+                tracing::warn!(error = %e, "Cluster can't be checked.");
+                PadOutput::TempError
+            }
         }
     }
 }
+
+fn status_output(
+    status: DeploymentStatus,
+    depl_in_progress: DataByte,
+    unknown: DataByte,
+    degraded: Option<DataByte>,
+) -> PadOutput {
+    match status {
+        DeploymentStatus::OK => PadOutput::Ok,
+        DeploymentStatus::NonOK => PadOutput::NotOk,
+        DeploymentStatus::Degraded => degraded.map_or(PadOutput::NotOk, PadOutput::Custom),
+        DeploymentStatus::InProgress => PadOutput::Custom(depl_in_progress),
+        DeploymentStatus::Unknown => PadOutput::Custom(unknown),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::StatusCode;
+    use k8s_openapi::api::apps::v1::Deployment;
+    use k8s_openapi::api::core::v1::{Event, Node, Pod};
+    use k8s_openapi::chrono::{self, SecondsFormat, Utc};
+    use serde_json::json;
+
+    use crate::kubernetes::model::{DeploymentName, Namespace};
+    use crate::testing::fixtures::emitted;
+    use crate::testing::kube_stub::KubeStub;
+
+    use super::*;
+
+    const NAMESPACE: &str = "default";
+
+    fn color(value: u8) -> DataByte {
+        DataByte::from_u8(value).unwrap()
+    }
+
+    fn deployment_id(stub: &KubeStub) -> DeploymentId {
+        DeploymentId {
+            context: stub.context(),
+            namespace: Namespace(NAMESPACE.to_string()),
+            deployment: DeploymentName("redis".to_string()),
+        }
+    }
+
+    fn deployment_config(stub: &KubeStub, check_pods: bool) -> Config {
+        Config {
+            deployment_id: deployment_id(stub),
+            replicas: ReplicaThresholds {
+                degraded_below: Some(1.0),
+                not_ok_below: 0.0,
+            },
+            unknown: color(0x71),
+            depl_in_progress: color(0x70),
+            degraded: Some(color(0x72)),
+            check_pods,
+            crashing: Some(color(0x73)),
+        }
+    }
+
+    fn deployment_handler(stub: &KubeStub, check_pods: bool) -> K8SDeploymentHandler {
+        K8SDeploymentHandler::new(stub.client(), deployment_config(stub, check_pods))
+    }
+
+    fn deployment(name: &str, ready: i32) -> Deployment {
+        serde_json::from_value(json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": { "name": name, "namespace": NAMESPACE, "labels": { "tier": "backend" } },
+            "spec": {
+                "replicas": 3,
+                "selector": { "matchLabels": { "app": name } },
+                "template": {},
+            },
+            "status": {
+                "readyReplicas": ready,
+                "updatedReplicas": 3,
+                "unavailableReplicas": 3 - ready,
+                "conditions": [
+                    { "type": "Available", "status": "True" },
+                    { "type": "Progressing", "status": "True" },
+                ],
+            },
+        }))
+        .unwrap()
+    }
+
+    fn pod(name: &str, restarts: i32, waiting_reason: Option<&str>) -> Pod {
+        let state = match waiting_reason {
+            Some(reason) => json!({ "waiting": { "reason": reason } }),
+            None => json!({ "running": {} }),
+        };
+        serde_json::from_value(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": { "name": name, "namespace": NAMESPACE, "labels": { "app": "redis" } },
+            "status": {
+                "phase": "Running",
+                "containerStatuses": [{
+                    "name": "redis",
+                    "image": "redis",
+                    "imageID": "",
+                    "ready": waiting_reason.is_none(),
+                    "restartCount": restarts,
+                    "state": state,
+                }],
+            },
+        }))
+        .unwrap()
+    }
+
+    fn event(pod_name: &str, reason: &str, count: i32, age_seconds: i64) -> Event {
+        let last_timestamp = (Utc::now() - chrono::Duration::seconds(age_seconds))
+            .to_rfc3339_opts(SecondsFormat::Secs, true);
+        serde_json::from_value(json!({
+            "apiVersion": "v1",
+            "kind": "Event",
+            "metadata": { "name": format!("{pod_name}.{reason}"), "namespace": NAMESPACE },
+            "involvedObject": { "kind": "Pod", "name": pod_name },
+            "reason": reason,
+            "type": "Warning",
+            "count": count,
+            "lastTimestamp": last_timestamp,
+        }))
+        .unwrap()
+    }
+
+    fn node(name: &str, ready: &str, memory_pressure: &str) -> Node {
+        serde_json::from_value(json!({
+            "apiVersion": "v1",
+            "kind": "Node",
+            "metadata": { "name": name },
+            "status": {
+                "conditions": [
+                    { "type": "Ready", "status": ready },
+                    { "type": "MemoryPressure", "status": memory_pressure },
+                ],
+            },
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn deployment_handler_reflects_ready_replicas() {
+        let stub = KubeStub::start().await;
+        let mut handler = deployment_handler(&stub, false);
+
+        stub.put(&deployment("redis", 3));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.put(&deployment("redis", 1));
+        assert_eq!(handler.handle().await, PadOutput::Custom(color(0x72)));
+
+        stub.put(&deployment("redis", 0));
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+    }
+
+    #[tokio::test]
+    async fn deployment_handler_reports_missing_deployment_as_temp_error() {
+        let stub = KubeStub::start().await;
+        let mut handler = deployment_handler(&stub, false);
+
+        assert_eq!(handler.handle().await, PadOutput::TempError);
+    }
+
+    #[tokio::test]
+    async fn deployment_handler_detects_restarted_containers() {
+        let stub = KubeStub::start().await;
+        let mut handler = deployment_handler(&stub, true);
+        stub.put(&deployment("redis", 3));
+
+        stub.put(&pod("redis-5d8f7c-x2bqz", 2, None));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.put(&pod("redis-5d8f7c-x2bqz", 3, None));
+        assert_eq!(handler.handle().await, PadOutput::Custom(color(0x73)));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+    }
+
+    #[tokio::test]
+    async fn deployment_handler_detects_oom_killed_containers() {
+        let stub = KubeStub::start().await;
+        let mut handler = deployment_handler(&stub, true);
+        stub.put(&deployment("redis", 3));
+
+        let mut killed = pod("redis-5d8f7c-x2bqz", 1, None);
+        let container = &mut killed
+            .status
+            .as_mut()
+            .unwrap()
+            .container_statuses
+            .as_mut()
+            .unwrap()[0];
+        container.last_state = Some(
+            serde_json::from_value(json!({
+                "terminated": { "reason": "OOMKilled", "exitCode": 137 },
+            }))
+            .unwrap(),
+        );
+        stub.put(&killed);
+        assert_eq!(handler.handle().await, PadOutput::Custom(color(0x73)));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+    }
+
+    #[tokio::test]
+    async fn deployment_handler_ignores_restarts_while_failed() {
+        let stub = KubeStub::start().await;
+        let mut handler = deployment_handler(&stub, true);
+        stub.put(&deployment("redis", 3));
+        stub.put(&pod("redis-5d8f7c-x2bqz", 2, None));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.put(&deployment("redis", 0));
+        stub.put(&pod("redis-5d8f7c-x2bqz", 5, None));
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+
+        stub.put(&deployment("redis", 3));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.put(&pod("redis-5d8f7c-x2bqz", 6, None));
+        assert_eq!(handler.handle().await, PadOutput::Custom(color(0x73)));
+    }
+
+    #[tokio::test]
+    async fn deployment_handler_detects_crash_loop() {
+        let stub = KubeStub::start().await;
+        let mut handler = deployment_handler(&stub, true);
+        stub.put(&deployment("redis", 3));
+
+        stub.put(&pod("redis-5d8f7c-x2bqz", 0, Some("CrashLoopBackOff")));
+        assert_eq!(handler.handle().await, PadOutput::Custom(color(0x73)));
+
+        stub.delete::<Pod>(Some(NAMESPACE), "redis-5d8f7c-x2bqz");
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+    }
+
+    #[tokio::test]
+    async fn namespace_handler_tolerates_unhealthy_workloads() {
+        let stub = KubeStub::start().await;
+        let config = |max_unhealthy| NamespaceConfig {
+            namespace_id: NamespaceId {
+                context: stub.context(),
+                namespace: Namespace(NAMESPACE.to_string()),
+            },
+            label_selector: Some("tier=backend".to_string()),
+            max_unhealthy,
+            unknown: color(0x71),
+            depl_in_progress: color(0x70),
+        };
+        stub.put(&deployment("redis", 3));
+        stub.put(&deployment("postgres", 0));
+        // Pod without `tier` label isn't selected.
+        stub.put(&pod("redis-5d8f7c-x2bqz", 0, Some("CrashLoopBackOff")));
+        // Pod of the Deployment is reflected only by the Deployment.
+        let mut owned = pod("postgres-7c9d8b-k4jxp", 0, Some("CrashLoopBackOff"));
+        owned.metadata.labels = Some([("tier".to_string(), "backend".to_string())].into());
+        owned.metadata.owner_references = Some(vec![serde_json::from_value(json!({
+            "apiVersion": "apps/v1",
+            "kind": "ReplicaSet",
+            "name": "postgres-7c9d8b",
+            "uid": "1",
+            "controller": true,
+        }))
+        .unwrap()]);
+        stub.put(&owned);
+
+        let mut strict = K8SNamespaceHandler::new(stub.client(), config(0));
+        assert_eq!(strict.detail(), None);
+        assert_eq!(strict.handle().await, PadOutput::NotOk);
+        assert_eq!(
+            strict.detail().as_deref(),
+            Some("1 of 2 workloads unhealthy")
+        );
+
+        let mut tolerant = K8SNamespaceHandler::new(stub.client(), config(1));
+        assert_eq!(tolerant.handle().await, PadOutput::Ok);
+    }
+
+    #[tokio::test]
+    async fn events_handler_counts_warnings_in_window() {
+        let stub = KubeStub::start().await;
+        let mut handler = K8SEventsHandler::new(
+            stub.client(),
+            EventsConfig {
+                workload_id: deployment_id(&stub),
+                reasons: vec!["BackOff".to_string()],
+                window: Duration::from_secs(60),
+                threshold: 1,
+            },
+        );
+
+        stub.put(&deployment("redis", 3));
+        stub.put(&pod("redis-5d8f7c-x2bqz", 0, None));
+        // Pod named after the deployment, but not selected by it.
+        let mut unrelated = pod("redis-cache-6b7d9f-p8wqn", 0, None);
+        unrelated.metadata.labels = Some([("app".to_string(), "redis-cache".to_string())].into());
+        stub.put(&unrelated);
+
+        // Occurrences before the first invocation aren't counted.
+        stub.put(&event("redis-5d8f7c-x2bqz", "BackOff", 5, 0));
+        stub.put(&event("redis-5d8f7c-x2bqz", "FailedMount", 5, 0));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.put(&event("redis-5d8f7c-x2bqz", "BackOff", 6, 0));
+        stub.put(&event("redis-cache-6b7d9f-p8wqn", "BackOff", 3, 0));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.put(&event("redis-5d8f7c-x2bqz", "BackOff", 7, 0));
+        stub.put(&event("redis-cache-6b7d9f-p8wqn", "BackOff", 9, 0));
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+    }
+
+    #[tokio::test]
+    async fn nodes_handler_reflects_node_conditions() {
+        let stub = KubeStub::start().await;
+        let mut handler = K8SClusterHandler::new(
+            stub.client(),
+            ClusterConfig {
+                context: stub.context(),
+                check: ClusterCheck::Nodes,
+                degraded: color(0x72),
+            },
+        );
+
+        stub.put(&node("node-1", "True", "False"));
+        stub.put(&node("node-2", "True", "False"));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.put(&node("node-2", "True", "True"));
+        assert_eq!(handler.handle().await, PadOutput::Custom(color(0x72)));
+
+        stub.put(&node("node-1", "False", "False"));
+        stub.put(&node("node-2", "Unknown", "False"));
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+    }
+
+    #[tokio::test]
+    async fn api_server_handler_reflects_readiness() {
+        let stub = KubeStub::start().await;
+        let mut handler = K8SClusterHandler::new(
+            stub.client(),
+            ClusterConfig {
+                context: stub.context(),
+                check: ClusterCheck::ApiServer,
+                degraded: color(0x72),
+            },
+        );
+
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.set_readyz(StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(handler.handle().await, PadOutput::Custom(color(0x72)));
+
+        // Expired token or revoked permissions hide the readiness.
+        stub.set_readyz(StatusCode::FORBIDDEN);
+        assert_eq!(handler.handle().await, PadOutput::Custom(color(0x72)));
+
+        stub.stop();
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+    }
+
+    #[tokio::test]
+    async fn deployment_watch_emits_changes() {
+        let stub = KubeStub::start().await;
+        stub.put(&deployment("redis", 3));
+        let watch = K8SDeploymentWatch::new(stub.client(), deployment_config(&stub, false));
+        let (sink, commands) = PadOutputSink::detached();
+        let running = tokio::spawn(Box::new(watch).run(sink));
+
+        assert_eq!(emitted(&commands).await, PadOutput::Ok);
+        // Stub doesn't replay changes made before the watch request, which follows the initial list.
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        stub.put(&deployment("redis", 2));
+        assert_eq!(emitted(&commands).await, PadOutput::Custom(color(0x72)));
+
+        stub.delete::<Deployment>(Some(NAMESPACE), "redis");
+        assert_eq!(emitted(&commands).await, PadOutput::NotOk);
+
+        running.abort();
+    }
+
+    #[tokio::test]
+    async fn deployment_watch_reports_missing_deployment() {
+        let stub = KubeStub::start().await;
+        let watch = K8SDeploymentWatch::new(stub.client(), deployment_config(&stub, false));
+        let (sink, commands) = PadOutputSink::detached();
+        let running = tokio::spawn(Box::new(watch).run(sink));
+
+        assert_eq!(emitted(&commands).await, PadOutput::NotOk);
+
+        running.abort();
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
+
+use crate::worker::actor::{PadEventSource, PadOutput, PadOutputSink};
+
+/// Interval of checking the file for appended lines and rotation.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct Config {
+    pub path: PathBuf,
+    pub pattern: Regex,
+    pub window: Duration,
+    /// Number of matching lines in the window which is still OK.
+    pub threshold: u32,
+    /// Pad returns to OK when no line matches for this duration.
+    pub quiet: Duration,
+    pub matched: PadOutput,
+}
+
+#[derive(Default)]
+struct TailState {
+    /// Times of matching lines within the window.
+    matches: VecDeque<Instant>,
+    /// Time of the last matching line, while the threshold is exceeded.
+    alerting: Option<Instant>,
+}
+
+/// Follows the log file, including its rotation, and reports lines matching the pattern.
+/// Output is emitted as soon as the threshold is exceeded, and once the quiet period passes.
+pub struct LogTailSource {
+    config: Config,
+}
+
+impl LogTailSource {
+    pub fn new(config: Config) -> LogTailSource {
+        LogTailSource { config }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadEventSource for LogTailSource {
+    async fn run(self: Box<Self>, sink: PadOutputSink) {
+        let config = self.config;
+        let mut state = TailState::default();
+        let mut emitted = None;
+        // Lines written before the start are not reported.
+        let mut followed = open(&config, true).await;
+        let mut line = Vec::new();
+
+        loop {
+            match &mut followed {
+                Ok(current) => {
+                    if let Err(e) = read_lines(&config, &mut state, current, &mut line).await {
+                        followed = Err(e);
+                    } else if rotated(&config, current).await {
+                        line.clear();
+                        followed = open(&config, false).await;
+                    }
+                }
+                // Missing file is followed from its beginning once it's created.
+                Err(_) => followed = open(&config, false).await,
+            }
+
+            let output = match &followed {
+                Ok(_) => state.output(&config),
+                Err(_) => PadOutput::TempError,
+            };
+            if emitted.as_ref() != Some(&output) {
+                if let Err(e) = &followed {
+                    tracing::warn!(error = %e, "Log file can't be followed.");
+                }
+                sink.emit(output.clone());
+                emitted = Some(output);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl TailState {
+    fn matched(&mut self, config: &Config) {
+        let now = Instant::now();
+        self.matches.push_back(now);
+        while let Some(oldest) = self.matches.front() {
+            if now.duration_since(*oldest) <= config.window {
+                break;
+            }
+            self.matches.pop_front();
+        }
+
+        let exceeded = self.matches.len() > config.threshold as usize;
+        if exceeded || self.alerting.is_some() {
+            self.alerting = Some(now);
+        }
+    }
+
+    /// Matched output lasts until no line matches for the quiet period.
+    fn output(&mut self, config: &Config) -> PadOutput {
+        match self.alerting {
+            Some(last_match) if last_match.elapsed() < config.quiet => config.matched.clone(),
+            _ => {
+                self.alerting = None;
+                PadOutput::Ok
+            }
+        }
+    }
+}
+
+/// Position in the followed file.
+struct Followed {
+    reader: BufReader<File>,
+    inode: u64,
+    position: u64,
+}
+
+async fn open(config: &Config, from_end: bool) -> std::io::Result<Followed> {
+    let mut file = File::open(&config.path).await?;
+    let inode = file.metadata().await?.ino();
+    let position = if from_end {
+        file.seek(SeekFrom::End(0)).await?
+    } else {
+        0
+    };
+    Ok(Followed {
+        reader: BufReader::new(file),
+        inode,
+        position,
+    })
+}
+
+async fn read_lines(
+    config: &Config,
+    state: &mut TailState,
+    followed: &mut Followed,
+    line: &mut Vec<u8>,
+) -> std::io::Result<()> {
+    loop {
+        let read = followed.reader.read_until(b'\n', line).await?;
+        followed.position += read as u64;
+        // Incomplete line is finished by the next read.
+        if read == 0 || line.last() != Some(&b'\n') {
+            return Ok(());
+        }
+        if config.pattern.is_match(&String::from_utf8_lossy(line)) {
+            state.matched(config);
+        }
+        line.clear();
+    }
+}
+
+/// File has been replaced with a new one, or truncated.
+async fn rotated(config: &Config, followed: &Followed) -> bool {
+    match tokio::fs::metadata(&config.path).await {
+        Ok(metadata) => metadata.ino() != followed.inode || metadata.len() < followed.position,
+        // File is moved away, but the new one doesn't exist yet.
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use crate::testing::fixtures::{emitted, TempDir};
+    use crate::worker::actor::Command;
+
+    use super::*;
+
+    fn temp_log(directory: &TempDir) -> PathBuf {
+        let path = directory.path().join("app.log");
+        std::fs::write(&path, "ERROR written before start\n").unwrap();
+        path
+    }
+
+    fn append(path: &Path, content: &str) {
+        let mut file = std::fs::File::options().append(true).open(path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    fn start(path: &Path, quiet: Duration) -> async_channel::Receiver<Command> {
+        let source = LogTailSource::new(Config {
+            path: path.to_path_buf(),
+            pattern: Regex::new("ERROR").unwrap(),
+            window: Duration::from_secs(60),
+            threshold: 1,
+            quiet,
+            matched: PadOutput::NotOk,
+        });
+        let (sink, commands) = PadOutputSink::detached();
+        tokio::spawn(Box::new(source).run(sink));
+        commands
+    }
+
+    #[tokio::test]
+    async fn matching_lines_over_threshold_are_emitted() {
+        let directory = TempDir::new("log-tail");
+        let path = temp_log(&directory);
+        let commands = start(&path, Duration::from_secs(60));
+        assert_eq!(emitted(&commands).await, PadOutput::Ok);
+
+        append(&path, "INFO started\nERROR first\n");
+        tokio::time::sleep(3 * POLL_INTERVAL).await;
+        assert!(commands.is_empty());
+
+        append(&path, "ERROR second\n");
+        assert_eq!(emitted(&commands).await, PadOutput::NotOk);
+    }
+
+    #[tokio::test]
+    async fn follows_rotated_file_and_quiets_down() {
+        let directory = TempDir::new("log-rotate");
+        let path = temp_log(&directory);
+        let commands = start(&path, Duration::from_secs(2));
+        assert_eq!(emitted(&commands).await, PadOutput::Ok);
+
+        std::fs::rename(&path, path.with_extension("log.1")).unwrap();
+        std::fs::write(&path, "ERROR first\nERROR second\n").unwrap();
+        assert_eq!(emitted(&commands).await, PadOutput::NotOk);
+        assert_eq!(emitted(&commands).await, PadOutput::Ok);
+    }
+
+    #[tokio::test]
+    async fn missing_file_is_temp_error() {
+        let directory = TempDir::new("log-missing");
+        let commands = start(&directory.path().join("app.log"), Duration::from_secs(2));
+        assert_eq!(emitted(&commands).await, PadOutput::TempError);
+    }
+}
@@ -1,3 +1,14 @@
 pub mod actor;
+pub mod alertmanager;
+pub mod bank;
+pub mod certificate;
+pub mod composite;
+pub mod file;
 pub mod k8s;
+pub mod log_tail;
+pub mod prometheus;
 pub mod script;
+pub mod shared;
+pub mod system;
+pub mod tcp;
+pub mod webhook;
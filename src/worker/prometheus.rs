@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::Url;
+use serde::de::IgnoredAny;
+
+use crate::worker::actor::{PadHandler, PadOutput};
+
+pub struct Threshold {
+    pub above: f64,
+    pub output: PadOutput,
+}
+
+pub struct Config {
+    /// Instant query endpoint, e.g. `http://prometheus:9090/api/v1/query`.
+    pub url: Url,
+    pub query: String,
+    pub headers: HeaderMap,
+    pub timeout: Duration,
+    pub empty: PadOutput,
+    pub non_empty: PadOutput,
+    /// Thresholds of the highest sample value, ordered from the highest one.
+    /// When set, they are used instead of `non_empty`.
+    pub thresholds: Vec<Threshold>,
+}
+
+#[derive(serde::Deserialize)]
+struct QueryResponse {
+    data: QueryData,
+}
+
+/// Sample value is a pair of timestamp and stringified number.
+type SampleValue = (IgnoredAny, String);
+
+#[derive(serde::Deserialize)]
+struct VectorSample {
+    value: SampleValue,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(tag = "resultType", content = "result", rename_all = "lowercase")]
+enum QueryData {
+    Vector(Vec<VectorSample>),
+    Scalar(SampleValue),
+}
+
+impl QueryData {
+    fn values(&self) -> Vec<f64> {
+        let samples = match self {
+            QueryData::Vector(samples) => samples.iter().map(|sample| &sample.value).collect(),
+            QueryData::Scalar(value) => vec![value],
+        };
+        samples
+            .into_iter()
+            .filter_map(|(_, value)| value.parse().ok())
+            .collect()
+    }
+}
+
+/// Runs PromQL instant query and reflects its result.
+pub struct PrometheusHandler {
+    client: reqwest::Client,
+    config: Config,
+}
+
+impl PrometheusHandler {
+    pub fn new(config: Config) -> PrometheusHandler {
+        PrometheusHandler {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    async fn query(&self) -> anyhow::Result<QueryData> {
+        let response: QueryResponse = self
+            .client
+            .get(self.config.url.clone())
+            .query(&[("query", &self.config.query)])
+            .headers(self.config.headers.clone())
+            .timeout(self.config.timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.data)
+    }
+
+    fn output(&self, data: &QueryData) -> PadOutput {
+        let values = data.values();
+        let Some(highest) = values.into_iter().reduce(f64::max) else {
+            return self.config.empty.clone();
+        };
+        if self.config.thresholds.is_empty() {
+            return self.config.non_empty.clone();
+        }
+
+        self.config
+            .thresholds
+            .iter()
+            .find(|threshold| highest > threshold.above)
+            .map_or(PadOutput::Ok, |threshold| threshold.output.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for PrometheusHandler {
+    async fn handle(&mut self) -> PadOutput {
+        match self.query().await {
+            Ok(data) => self.output(&data),
+            Err(e) => {
+                tracing::warn!(error = %e, "Query failed.");
+                PadOutput::TempError
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::StatusCode;
+
+    use crate::midi::model::DataByte;
+    use crate::testing::http_stub::HttpStub;
+
+    use super::*;
+
+    const QUERY_PATH: &str = "/api/v1/query";
+
+    fn config(stub: &HttpStub, thresholds: Vec<Threshold>) -> Config {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer secret".parse().unwrap());
+        Config {
+            url: Url::parse(&stub.url()).unwrap().join(QUERY_PATH).unwrap(),
+            query: "up == 0".to_string(),
+            headers,
+            timeout: Duration::from_millis(200),
+            empty: PadOutput::Ok,
+            non_empty: PadOutput::NotOk,
+            thresholds,
+        }
+    }
+
+    fn vector(values: &[&str]) -> String {
+        let samples: Vec<String> = values
+            .iter()
+            .map(|value| format!(r#"{{"metric": {{}}, "value": [1700000000.1, "{value}"]}}"#))
+            .collect();
+        format!(
+            r#"{{"status": "success", "data": {{"resultType": "vector", "result": [{}]}}}}"#,
+            samples.join(",")
+        )
+    }
+
+    #[tokio::test]
+    async fn reflects_empty_and_non_empty_vector() {
+        let stub = HttpStub::start().await;
+        let mut handler = PrometheusHandler::new(config(&stub, vec![]));
+
+        stub.respond(QUERY_PATH, StatusCode::OK, &vector(&[]));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.respond(QUERY_PATH, StatusCode::OK, &vector(&["0"]));
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+    }
+
+    #[tokio::test]
+    async fn sends_query_and_headers() {
+        let stub = HttpStub::start().await;
+        let mut handler = PrometheusHandler::new(config(&stub, vec![]));
+        stub.respond(QUERY_PATH, StatusCode::OK, &vector(&[]));
+
+        handler.handle().await;
+
+        let request = &stub.requests()[0];
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.query["query"], "up == 0");
+        assert_eq!(request.headers["authorization"], "Bearer secret");
+    }
+
+    #[tokio::test]
+    async fn maps_highest_value_with_thresholds() {
+        let stub = HttpStub::start().await;
+        let warning = DataByte::from_u8(0x72).unwrap();
+        let thresholds = vec![
+            Threshold {
+                above: 0.9,
+                output: PadOutput::NotOk,
+            },
+            Threshold {
+                above: 0.7,
+                output: PadOutput::Custom(warning),
+            },
+        ];
+        let mut handler = PrometheusHandler::new(config(&stub, thresholds));
+
+        stub.respond(QUERY_PATH, StatusCode::OK, &vector(&["0.1", "0.5"]));
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+
+        stub.respond(QUERY_PATH, StatusCode::OK, &vector(&["0.1", "0.8"]));
+        assert_eq!(handler.handle().await, PadOutput::Custom(warning));
+
+        let scalar = r#"{"status": "success", "data": {"resultType": "scalar", "result": [1700000000.1, "0.95"]}}"#;
+        stub.respond(QUERY_PATH, StatusCode::OK, scalar);
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+    }
+
+    #[tokio::test]
+    async fn reports_failed_query_as_temp_error() {
+        let stub = HttpStub::start().await;
+        let mut handler = PrometheusHandler::new(config(&stub, vec![]));
+
+        let error = r#"{"status": "error", "errorType": "bad_data", "error": "parse error"}"#;
+        stub.respond(QUERY_PATH, StatusCode::BAD_REQUEST, error);
+        assert_eq!(handler.handle().await, PadOutput::TempError);
+
+        stub.respond(QUERY_PATH, StatusCode::OK, &vector(&[]));
+        stub.set_delay(Duration::from_secs(1));
+        assert_eq!(handler.handle().await, PadOutput::TempError);
+    }
+}
@@ -29,14 +29,23 @@ impl PadHandler for ScriptHandler {
             .await;
 
         match command {
-            Ok(status) => {
-                if status.status.success() {
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let stderr = stderr.trim_end();
+                if output.status.success() {
+                    if !stderr.is_empty() {
+                        tracing::debug!(stderr, "Script succeeded.");
+                    }
                     PadOutput::Ok
                 } else {
+                    tracing::warn!(status = %output.status, stderr, "Script failed.");
                     PadOutput::NotOk
                 }
             }
-            Err(_) => PadOutput::TempError,
+            Err(e) => {
+                tracing::warn!(error = %e, "Script can't be run.");
+                PadOutput::TempError
+            }
         }
     }
 }
@@ -0,0 +1,49 @@
+use std::time::{Duration, Instant};
+
+use crate::worker::actor::{PadHandler, PadOutput, PressOutcome};
+
+/// Handler shared by several pads, possibly on different controllers.
+/// Result of the inner handler is reused by all of them as long as it is not older than `fresh_for`.
+pub struct SharedHandler {
+    inner: Box<dyn PadHandler>,
+    fresh_for: Duration,
+    last_output: Option<(Instant, PadOutput)>,
+}
+
+impl SharedHandler {
+    pub fn new(inner: Box<dyn PadHandler>, fresh_for: Duration) -> SharedHandler {
+        SharedHandler {
+            inner,
+            fresh_for,
+            last_output: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for SharedHandler {
+    async fn handle(&mut self) -> PadOutput {
+        if let Some((produced_at, output)) = &self.last_output {
+            if produced_at.elapsed() < self.fresh_for {
+                return output.clone();
+            }
+        }
+
+        let output = self.inner.handle().await;
+        self.last_output = Some((Instant::now(), output.clone()));
+        output
+    }
+
+    fn detail(&self) -> Option<String> {
+        self.inner.detail()
+    }
+
+    async fn pressed(&mut self) -> PressOutcome {
+        let outcome = self.inner.pressed().await;
+        if outcome == PressOutcome::Handled {
+            // Press may have changed the result, so it is not reused.
+            self.last_output = None;
+        }
+        outcome
+    }
+}
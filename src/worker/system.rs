@@ -0,0 +1,253 @@
+use std::path::{Path, PathBuf};
+
+use crate::worker::actor::{PadHandler, PadOutput};
+use crate::worker::prometheus::Threshold;
+
+const PROC: &str = "/proc";
+
+#[derive(Clone, Copy)]
+pub enum LoadPeriod {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+}
+
+pub enum Metric {
+    /// Used space of the filesystem mounted at the path, in percent.
+    Disk(PathBuf),
+    /// Used memory in percent, i.e. memory not available for starting new applications.
+    Memory,
+    /// Load average, optionally divided by the number of CPUs.
+    Load { period: LoadPeriod, per_cpu: bool },
+}
+
+pub enum Config {
+    Usage {
+        metric: Metric,
+        /// Thresholds of the metric, ordered from the highest one.
+        thresholds: Vec<Threshold>,
+    },
+    /// Checks whether a process with the name runs.
+    /// Name is compared with the executable name of the process, which isn't truncated unlike `/proc/<pid>/comm`.
+    Process { name: String, missing: PadOutput },
+}
+
+/// Checks resources of the machine running the controller.
+pub struct SystemHandler {
+    config: Config,
+}
+
+impl SystemHandler {
+    pub fn new(config: Config) -> SystemHandler {
+        SystemHandler { config }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for SystemHandler {
+    async fn handle(&mut self) -> PadOutput {
+        match &self.config {
+            Config::Usage { metric, thresholds } => match measure(metric).await {
+                Ok(value) => thresholds
+                    .iter()
+                    .find(|threshold| value > threshold.above)
+                    .map_or(PadOutput::Ok, |threshold| threshold.output.clone()),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Usage can't be measured.");
+                    PadOutput::TempError
+                }
+            },
+            Config::Process { name, missing } => match process_running(Path::new(PROC), name).await
+            {
+                Ok(true) => PadOutput::Ok,
+                Ok(false) => missing.clone(),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Processes can't be listed.");
+                    PadOutput::TempError
+                }
+            },
+        }
+    }
+}
+
+async fn measure(metric: &Metric) -> anyhow::Result<f64> {
+    match metric {
+        Metric::Disk(mount) => {
+            let mount = mount.clone();
+            // Unresponsive network filesystems block the call.
+            tokio::task::spawn_blocking(move || disk_usage(&mount)).await?
+        }
+        Metric::Memory => {
+            memory_usage(&tokio::fs::read_to_string(Path::new(PROC).join("meminfo")).await?)
+        }
+        Metric::Load { period, per_cpu } => {
+            let load = load_average(
+                &tokio::fs::read_to_string(Path::new(PROC).join("loadavg")).await?,
+                *period,
+            )?;
+            if *per_cpu {
+                Ok(load / std::thread::available_parallelism()?.get() as f64)
+            } else {
+                Ok(load)
+            }
+        }
+    }
+}
+
+fn disk_usage(mount: &Path) -> anyhow::Result<f64> {
+    let stat = nix::sys::statvfs::statvfs(mount)?;
+    // Same as `df`, blocks reserved for root count neither as used nor as available.
+    let used = (stat.blocks() - stat.blocks_free()) as f64;
+    let total = used + stat.blocks_available() as f64;
+    if total == 0.0 {
+        anyhow::bail!("Filesystem at {} has no blocks.", mount.display());
+    }
+    Ok(100.0 * used / total)
+}
+
+fn memory_usage(meminfo: &str) -> anyhow::Result<f64> {
+    let field = |name: &str| -> anyhow::Result<f64> {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| value.split_whitespace().next()?.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("Missing {name} in meminfo."))
+    };
+    let total = field("MemTotal")?;
+    if total == 0.0 {
+        anyhow::bail!("MemTotal is zero.");
+    }
+    Ok(100.0 * (1.0 - field("MemAvailable")? / total))
+}
+
+fn load_average(loadavg: &str, period: LoadPeriod) -> anyhow::Result<f64> {
+    let index = match period {
+        LoadPeriod::OneMinute => 0,
+        LoadPeriod::FiveMinutes => 1,
+        LoadPeriod::FifteenMinutes => 2,
+    };
+    loadavg
+        .split_whitespace()
+        .nth(index)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| anyhow::anyhow!("Unexpected loadavg '{}'.", loadavg.trim()))
+}
+
+async fn process_running(proc: &Path, name: &str) -> anyhow::Result<bool> {
+    let mut entries = tokio::fs::read_dir(proc).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+        {
+            continue;
+        }
+        // Process might have exited in the meantime.
+        if let Ok(cmdline) = tokio::fs::read(entry.path().join("cmdline")).await {
+            if executable_name(&cmdline).is_some_and(|executable| executable == name) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// File name of the first argument in `/proc/<pid>/cmdline`. Kernel threads have none.
+fn executable_name(cmdline: &[u8]) -> Option<&str> {
+    let program = cmdline.split(|b| *b == 0).next()?;
+    let program = std::str::from_utf8(program).ok()?;
+    Path::new(program).file_name()?.to_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::fixtures::warning;
+
+    use super::*;
+
+    const MEMINFO: &str = "MemTotal:       16000000 kB\n\
+                           MemFree:         2000000 kB\n\
+                           MemAvailable:    4000000 kB\n";
+
+    fn usage(metric: Metric) -> SystemHandler {
+        SystemHandler::new(Config::Usage {
+            metric,
+            // Any measured value exceeds the threshold.
+            thresholds: vec![Threshold {
+                above: -1.0,
+                output: warning(),
+            }],
+        })
+    }
+
+    #[test]
+    fn parses_memory_usage() {
+        assert_eq!(memory_usage(MEMINFO).unwrap(), 75.0);
+        assert!(memory_usage("MemTotal: 16000000 kB\n").is_err());
+    }
+
+    #[test]
+    fn parses_load_average() {
+        let loadavg = "0.52 1.25 2.50 1/123 4567\n";
+
+        assert_eq!(load_average(loadavg, LoadPeriod::OneMinute).unwrap(), 0.52);
+        assert_eq!(
+            load_average(loadavg, LoadPeriod::FiveMinutes).unwrap(),
+            1.25
+        );
+        assert_eq!(
+            load_average(loadavg, LoadPeriod::FifteenMinutes).unwrap(),
+            2.5
+        );
+        assert!(load_average("", LoadPeriod::OneMinute).is_err());
+    }
+
+    #[tokio::test]
+    async fn maps_usage_to_thresholds() {
+        let mut disk = usage(Metric::Disk(PathBuf::from("/")));
+        assert_eq!(disk.handle().await, warning());
+
+        let mut load = usage(Metric::Load {
+            period: LoadPeriod::OneMinute,
+            per_cpu: true,
+        });
+        assert_eq!(load.handle().await, warning());
+
+        let mut missing = usage(Metric::Disk(PathBuf::from("/nonexistent")));
+        assert_eq!(missing.handle().await, PadOutput::TempError);
+    }
+
+    #[tokio::test]
+    async fn finds_process_by_name() {
+        // Name of the test binary is longer than `/proc/<pid>/comm` keeps.
+        let cmdline = std::fs::read("/proc/self/cmdline").unwrap();
+        let own_name = executable_name(&cmdline).unwrap();
+        assert!(own_name.len() > 15);
+        let mut running = SystemHandler::new(Config::Process {
+            name: own_name.to_string(),
+            missing: warning(),
+        });
+        assert_eq!(running.handle().await, PadOutput::Ok);
+
+        let mut missing = SystemHandler::new(Config::Process {
+            name: "no-such-process".to_string(),
+            missing: warning(),
+        });
+        assert_eq!(missing.handle().await, warning());
+    }
+
+    #[test]
+    fn executable_name_is_file_name_of_first_argument() {
+        assert_eq!(
+            executable_name(b"/usr/sbin/prometheus-node-exporter\0--web.listen-address=:9100\0"),
+            Some("prometheus-node-exporter")
+        );
+        assert_eq!(
+            executable_name(b"nginx: worker process\0"),
+            Some("nginx: worker process")
+        );
+        assert_eq!(executable_name(b""), None);
+    }
+}
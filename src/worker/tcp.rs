@@ -0,0 +1,162 @@
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use tokio::net::TcpStream;
+use tokio_native_tls::TlsConnector;
+
+use crate::worker::actor::{PadHandler, PadOutput};
+
+pub struct Tls {
+    pub connector: TlsConnector,
+    /// Name verified against the server certificate.
+    pub server_name: String,
+}
+
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+    /// Limit of connecting, including TLS handshake.
+    pub timeout: Duration,
+    pub tls: Option<Tls>,
+    pub refused: PadOutput,
+    pub handshake_failed: PadOutput,
+    pub timed_out: PadOutput,
+}
+
+enum Failure {
+    Refused,
+    Handshake,
+    Other,
+}
+
+/// Checks whether the port accepts connections, optionally completing TLS handshake.
+pub struct TcpHandler {
+    config: Config,
+}
+
+impl TcpHandler {
+    pub fn new(config: Config) -> TcpHandler {
+        TcpHandler { config }
+    }
+
+    async fn connect(&self) -> Result<(), Failure> {
+        let stream = TcpStream::connect((self.config.host.as_str(), self.config.port))
+            .await
+            .map_err(|e| match e.kind() {
+                ErrorKind::ConnectionRefused => Failure::Refused,
+                _ => {
+                    tracing::warn!(error = %e, "Connection failed.");
+                    Failure::Other
+                }
+            })?;
+
+        if let Some(tls) = &self.config.tls {
+            tls.connector
+                .connect(&tls.server_name, stream)
+                .await
+                .map_err(|e| {
+                    tracing::warn!(error = %e, "TLS handshake failed.");
+                    Failure::Handshake
+                })?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl PadHandler for TcpHandler {
+    async fn handle(&mut self) -> PadOutput {
+        match tokio::time::timeout(self.config.timeout, self.connect()).await {
+            Ok(Ok(())) => PadOutput::Ok,
+            Ok(Err(Failure::Refused)) => self.config.refused.clone(),
+            Ok(Err(Failure::Handshake)) => self.config.handshake_failed.clone(),
+            Ok(Err(Failure::Other)) => PadOutput::TempError,
+            Err(_) => self.config.timed_out.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    use crate::midi::model::DataByte;
+    use crate::testing::certificates;
+
+    use super::*;
+
+    fn config(port: u16, tls: bool) -> Config {
+        let connector = native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap();
+        Config {
+            host: "127.0.0.1".to_string(),
+            port,
+            timeout: Duration::from_millis(300),
+            tls: tls.then(|| Tls {
+                connector: connector.into(),
+                server_name: "localhost".to_string(),
+            }),
+            refused: PadOutput::NotOk,
+            handshake_failed: PadOutput::Custom(DataByte::from_u8(0x73).unwrap()),
+            timed_out: PadOutput::Custom(DataByte::from_u8(0x72).unwrap()),
+        }
+    }
+
+    #[tokio::test]
+    async fn accepted_connection_is_ok() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut handler = TcpHandler::new(config(port, false));
+
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+    }
+
+    #[tokio::test]
+    async fn refused_connection_is_not_ok() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let mut handler = TcpHandler::new(config(port, false));
+
+        assert_eq!(handler.handle().await, PadOutput::NotOk);
+    }
+
+    #[tokio::test]
+    async fn completed_handshake_is_ok() {
+        let address = certificates::serve_tls(&certificates::self_signed(30)).await;
+        let mut handler = TcpHandler::new(config(address.port(), true));
+
+        assert_eq!(handler.handle().await, PadOutput::Ok);
+    }
+
+    #[tokio::test]
+    async fn stalled_handshake_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut handler = TcpHandler::new(config(port, true));
+
+        assert_eq!(
+            handler.handle().await,
+            PadOutput::Custom(DataByte::from_u8(0x72).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_handshake_is_reported() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _ = stream.write_all(b"SSH-2.0-OpenSSH\r\n").await;
+        });
+        let mut handler = TcpHandler::new(config(port, true));
+
+        assert_eq!(
+            handler.handle().await,
+            PadOutput::Custom(DataByte::from_u8(0x73).unwrap())
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::worker::actor::{PadEventSource, PadOutput, PadOutputSink};
+
+/// Display of the pad, whose state hasn't been updated within TTL.
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expired {
+    Initial,
+    #[default]
+    TransientError,
+}
+
+pub struct Config {
+    /// Name used in `POST /pads/{name}`.
+    pub name: String,
+    pub ttl: Option<Duration>,
+    pub expired: Expired,
+}
+
+/// Webhook pads by their names. Several pads may share the same name.
+#[derive(Clone, Default)]
+pub struct Webhooks(Arc<dashmap::DashMap<String, Vec<async_channel::Sender<PadOutput>>>>);
+
+impl Webhooks {
+    pub fn register(&self, name: &str) -> async_channel::Receiver<PadOutput> {
+        let (sender, receiver) = async_channel::unbounded();
+        self.0.entry(name.to_string()).or_default().push(sender);
+        receiver
+    }
+
+    /// Passes the state to all pads with the name. Returns whether there is any.
+    pub fn publish(&self, name: &str, output: PadOutput) -> bool {
+        match self.0.get(name) {
+            Some(senders) => {
+                for sender in senders.iter() {
+                    let _ = sender.try_send(output.clone());
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Unregisters all pads, e.g. before the configuration is reloaded.
+    pub fn clear(&self) {
+        self.0.clear();
+    }
+}
+
+/// Displays states pushed to the embedded server, e.g. by CI pipelines.
+pub struct WebhookSource {
+    config: Config,
+    updates: async_channel::Receiver<PadOutput>,
+}
+
+impl WebhookSource {
+    pub fn new(config: Config, webhooks: &Webhooks) -> WebhookSource {
+        WebhookSource {
+            updates: webhooks.register(&config.name),
+            config,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PadEventSource for WebhookSource {
+    async fn run(self: Box<Self>, sink: PadOutputSink) {
+        let mut expires = false;
+        loop {
+            let update = match self.config.ttl {
+                Some(ttl) if expires => tokio::time::timeout(ttl, self.updates.recv()).await,
+                _ => Ok(self.updates.recv().await),
+            };
+
+            match update {
+                Ok(Ok(output)) => {
+                    sink.emit(output);
+                    expires = true;
+                }
+                Ok(Err(_)) => return,
+                Err(_) => {
+                    match self.config.expired {
+                        Expired::Initial => sink.reset(),
+                        Expired::TransientError => sink.emit(PadOutput::TempError),
+                    }
+                    expires = false;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::midi::model::DataByte;
+    use crate::testing::fixtures::next_command;
+    use crate::worker::actor::Command;
+
+    use super::*;
+
+    fn start(webhooks: &Webhooks, expired: Expired) -> async_channel::Receiver<Command> {
+        let source = WebhookSource::new(
+            Config {
+                name: "deploy".to_string(),
+                ttl: Some(Duration::from_millis(200)),
+                expired,
+            },
+            webhooks,
+        );
+        let (sink, commands) = PadOutputSink::detached();
+        tokio::spawn(Box::new(source).run(sink));
+        commands
+    }
+
+    #[tokio::test]
+    async fn published_state_expires_after_ttl() {
+        let webhooks = Webhooks::default();
+        let reset = start(&webhooks, Expired::Initial);
+        let errored = start(&webhooks, Expired::TransientError);
+
+        let color = PadOutput::Custom(DataByte::from_u8(0x72).unwrap());
+        assert!(webhooks.publish("deploy", color.clone()));
+        assert!(!webhooks.publish("unknown", PadOutput::Ok));
+
+        for commands in [&reset, &errored] {
+            assert!(
+                matches!(next_command(commands).await, Command::Output(output) if output == color)
+            );
+        }
+        assert!(matches!(next_command(&reset).await, Command::Reset));
+        assert!(matches!(
+            next_command(&errored).await,
+            Command::Output(PadOutput::TempError)
+        ));
+    }
+}